@@ -0,0 +1,26 @@
+//
+// Fuzz `reduce` with arbitrary words over a small alphabet, checking
+// it doesn't panic and produces a valid reduction.
+//
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+use idem_monoid::{content, reduce, Word};
+
+// Small enough to get lots of repeated structure, big enough to
+// exercise the recursion.
+const ALPHABET_SIZE: u8 = 4;
+
+fuzz_target!(|data: &[u8]| {
+    let word = data.iter().map(|b| b % ALPHABET_SIZE).collect::<Word>();
+    let steps = reduce(&word);
+
+    assert_eq!(steps.start(), &word);
+    assert!(steps.end().len() <= word.len());
+    assert_eq!(content(steps.end()), content(&word));
+    if let Err(e) = steps.verify() {
+        panic!("Invalid reduction of {:?}: {}", word, e);
+    }
+});
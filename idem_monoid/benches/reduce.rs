@@ -0,0 +1,60 @@
+//
+// Benchmarks for the hot paths: reducing words, and generating the
+// monoid.
+//
+// The union-find `extend` is benchmarked in `original`'s own benches.
+//
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use idem_monoid::{generate_exact_monoid, reduce, reduce_word, Sym, Word};
+
+// A fixed pseudo-random word over the given number of letters, so
+// runs are comparable.
+fn random_word(len: usize, n_letters: usize, seed: u64) -> Word {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            // xorshift64
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % n_letters as u64) as Sym
+        })
+        .collect()
+}
+
+fn bench_reduce(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reduce");
+    for len in [10, 50, 200] {
+        let word = random_word(len, 3, 0x5eed + len as u64);
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &word, |b, word| {
+            b.iter(|| reduce(black_box(word)))
+        });
+    }
+    group.finish();
+}
+
+// The same words as `bench_reduce`, only finding the normal form, to
+// show the cost of building the steps.
+fn bench_reduce_word(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reduce_word");
+    for len in [10, 50, 200] {
+        let word = random_word(len, 3, 0x5eed + len as u64);
+        group.throughput(Throughput::Elements(len as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(len), &word, |b, word| {
+            b.iter(|| reduce_word(black_box(word)))
+        });
+    }
+    group.finish();
+}
+
+fn bench_generate(c: &mut Criterion) {
+    c.bench_function("generate_exact_monoid(3)", |b| {
+        b.iter(|| generate_exact_monoid(black_box(3)))
+    });
+}
+
+criterion_group!(benches, bench_reduce, bench_reduce_word, bench_generate);
+criterion_main!(benches);
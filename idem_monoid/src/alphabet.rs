@@ -0,0 +1,80 @@
+////////////////////////////////////////////////////////////////////////
+// Alphabets for writing words as strings.
+//
+
+use std::borrow::Cow;
+use std::error::Error;
+use std::fmt;
+
+use crate::{str_to_word_with, word_to_str_with, Word, WordRef};
+
+// The usual alphabet, 'a' for symbol 0, 'b' for 1, etc.
+const LOWERCASE: [char; 26] = [
+    'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
+    't', 'u', 'v', 'w', 'x', 'y', 'z',
+];
+
+/// A character in a string that isn't in the alphabet, and its
+/// position (in characters) in the string.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct InvalidLetter {
+    pub position: usize,
+    pub letter: char,
+}
+
+impl fmt::Display for InvalidLetter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid character '{}' at position {}",
+            self.letter,
+            self.position + 1
+        )
+    }
+}
+
+impl Error for InvalidLetter {}
+
+/// The characters used to write each symbol, so that words can be
+/// converted to and from strings.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Alphabet {
+    letters: Cow<'static, [char]>,
+}
+
+impl Alphabet {
+    /// The usual alphabet, writing symbol 0 as 'a', 1 as 'b', and so
+    /// on up to 'z', as used by `word_to_str` and `str_to_word`.
+    pub fn lowercase() -> Alphabet {
+        Alphabet {
+            letters: Cow::Borrowed(&LOWERCASE),
+        }
+    }
+
+    /// An alphabet writing symbol i as `letters[i]`. The letters
+    /// should be distinct.
+    pub fn new(letters: Vec<char>) -> Alphabet {
+        Alphabet {
+            letters: Cow::Owned(letters),
+        }
+    }
+
+    /// The letters, in symbol order.
+    pub fn letters(&self) -> &[char] {
+        &self.letters
+    }
+
+    /// Write a word as a string. Panics if a symbol has no letter.
+    pub fn encode(&self, word: WordRef) -> String {
+        word_to_str_with(&self.letters, word)
+    }
+
+    /// Read a word from a string, failing on the first character that
+    /// isn't in the alphabet.
+    pub fn decode(&self, s: &str) -> Result<Word, InvalidLetter> {
+        str_to_word_with(&self.letters, s).map_err(|letter| InvalidLetter {
+            position: s.chars().position(|c| c == letter).unwrap(),
+            letter,
+        })
+    }
+}
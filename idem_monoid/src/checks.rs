@@ -0,0 +1,124 @@
+////////////////////////////////////////////////////////////////////////
+// Sanity checks that the generated elements and reduction really do
+// form an idempotent monoid.
+//
+
+use std::collections::{HashMap, HashSet};
+
+use crate::rng::Rng;
+use crate::{generate_monoid, product, reduce, ConcatMode, Word, WordRef};
+
+/// Multiply every pair of elements of the monoid over n letters, and
+/// check each product reduces to an element. Returns the number of
+/// products checked, and the (left, right, product) triples whose
+/// product wasn't an element.
+pub fn check_closure(n_letters: usize) -> (usize, Vec<(Word, Word, Word)>) {
+    let elements = generate_monoid(n_letters);
+    let element_set: HashSet<&Word> = elements.iter().collect();
+
+    let mut checked = 0;
+    let mut failures = Vec::new();
+    for l in elements.iter() {
+        for r in elements.iter() {
+            let product = reduce(&product(l, r, ConcatMode::Raw)).end().clone();
+            if !element_set.contains(&product) {
+                failures.push((l.clone(), r.clone(), product));
+            }
+            checked += 1;
+        }
+    }
+
+    (checked, failures)
+}
+
+/// Check that every element of the monoid over n letters is
+/// idempotent, i.e. ww reduces to the same thing as w. Returns the
+/// number of elements checked, and the elements whose squares
+/// reduced to something else, along with what they reduced to.
+pub fn check_idempotent(n_letters: usize) -> (usize, Vec<(Word, Word)>) {
+    let elements = generate_monoid(n_letters);
+
+    let mut failures = Vec::new();
+    for w in elements.iter() {
+        let canonical = reduce(w).end().clone();
+        let square = reduce(&product(w, w, ConcatMode::Raw)).end().clone();
+        if square != canonical {
+            failures.push((w.clone(), square));
+        }
+    }
+
+    (elements.len(), failures)
+}
+
+/// An (a, b, c) triple for which (ab)c and a(bc) reduce differently,
+/// along with the two reductions.
+pub struct AssociativityFailure {
+    pub a: Word,
+    pub b: Word,
+    pub c: Word,
+    pub ab_c: Word,
+    pub a_bc: Word,
+}
+
+/// Check that reduce(reduce(ab)c) == reduce(a reduce(bc)) for all
+/// triples of elements of the monoid over n letters, or for the given
+/// number of randomly sampled triples. Returns the number of triples
+/// checked and the failures.
+pub fn check_associativity(
+    n_letters: usize,
+    sample: Option<usize>,
+) -> (usize, Vec<AssociativityFailure>) {
+    let elements = generate_monoid(n_letters);
+
+    // Many products recur, so cache them.
+    let mut cache: HashMap<(Word, Word), Word> = HashMap::new();
+    let mut product = |l: WordRef, r: WordRef| -> Word {
+        cache
+            .entry((l.to_vec(), r.to_vec()))
+            .or_insert_with(|| reduce(&product(l, r, ConcatMode::Raw)).end().clone())
+            .clone()
+    };
+
+    let mut failures = Vec::new();
+    let mut check = |a: &Word, b: &Word, c: &Word| {
+        let ab = product(a, b);
+        let ab_c = product(&ab, c);
+        let bc = product(b, c);
+        let a_bc = product(a, &bc);
+        if ab_c != a_bc {
+            failures.push(AssociativityFailure {
+                a: a.clone(),
+                b: b.clone(),
+                c: c.clone(),
+                ab_c,
+                a_bc,
+            });
+        }
+    };
+
+    let checked = match sample {
+        Some(n) => {
+            let mut rng = Rng::new(0);
+            let len = elements.len();
+            for _ in 0..n {
+                let a = &elements[rng.below(len)];
+                let b = &elements[rng.below(len)];
+                let c = &elements[rng.below(len)];
+                check(a, b, c);
+            }
+            n
+        }
+        None => {
+            for a in elements.iter() {
+                for b in elements.iter() {
+                    for c in elements.iter() {
+                        check(a, b, c);
+                    }
+                }
+            }
+            elements.len().pow(3)
+        }
+    };
+
+    (checked, failures)
+}
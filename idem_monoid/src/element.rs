@@ -0,0 +1,82 @@
+////////////////////////////////////////////////////////////////////////
+// Monoid elements, with multiplication
+//
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Mul;
+
+use crate::{leq, product, reduce, word_to_str, ConcatMode, Word, WordRef};
+
+/// An element of the idempotent monoid, held in canonical form so
+/// that equal elements compare equal. Elements are partially ordered
+/// by the natural order, as in `leq`; to sort them, compare their
+/// words with `shortlex_cmp`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MonoidElement(Word);
+
+impl MonoidElement {
+    /// The element represented by the given word, reducing it to
+    /// canonical form.
+    pub fn new(word: WordRef) -> MonoidElement {
+        MonoidElement(reduce(word).end().clone())
+    }
+
+    /// The identity element, the empty word.
+    pub fn identity() -> MonoidElement {
+        MonoidElement(Vec::new())
+    }
+
+    /// Is this element below the other in the natural partial order?
+    /// See `leq`; this is the order `PartialOrd` uses.
+    pub fn leq(&self, other: &MonoidElement) -> bool {
+        leq(&self.0, &other.0)
+    }
+
+    /// The canonical word for the element.
+    pub fn word(&self) -> &Word {
+        &self.0
+    }
+}
+
+impl From<MonoidElement> for Word {
+    fn from(element: MonoidElement) -> Word {
+        element.0
+    }
+}
+
+impl PartialOrd for MonoidElement {
+    fn partial_cmp(&self, other: &MonoidElement) -> Option<Ordering> {
+        if self == other {
+            Some(Ordering::Equal)
+        } else if self.leq(other) {
+            Some(Ordering::Less)
+        } else if other.leq(self) {
+            Some(Ordering::Greater)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for MonoidElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", word_to_str(&self.0))
+    }
+}
+
+impl Mul for &MonoidElement {
+    type Output = MonoidElement;
+
+    fn mul(self, rhs: &MonoidElement) -> MonoidElement {
+        MonoidElement::new(&product(&self.0, &rhs.0, ConcatMode::Raw))
+    }
+}
+
+impl Mul for MonoidElement {
+    type Output = MonoidElement;
+
+    fn mul(self, rhs: MonoidElement) -> MonoidElement {
+        &self * &rhs
+    }
+}
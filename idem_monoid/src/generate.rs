@@ -0,0 +1,290 @@
+////////////////////////////////////////////////////////////////////////
+// Monoid generation
+//
+
+use itertools::Itertools;
+
+use std::io::{self, BufWriter, Write};
+use std::rc::Rc;
+
+use crate::profile::{count_search, sym_eq};
+use crate::{chain, Sym, Word, WordRef};
+
+/// Generate the idempotent monoid of all the words using *exactly*
+/// the same n letters. e.g. "ab", "ba", "aba", "bab", but not "a" and
+/// "b". The letters used are the first n of the alphabet.
+pub fn generate_exact_monoid(n_letters: usize) -> Vec<Word> {
+    // Build each exact monoid from the previous one, rather than
+    // recursing, so each is only generated once.
+    (1..=n_letters).fold(vec![vec![]], |words, i| next_exact_monoid(&words, i))
+}
+
+// Given the exact monoid on (n-1) letters, generate the exact monoid
+// on n letters.
+fn next_exact_monoid(shorter_words: &[Word], n_letters: usize) -> Vec<Word> {
+    // Generate all the variants on this (n-1)-letter monoid by using
+    // all but one of the letter from the n-letter alphabet,
+    // generateing all the (n-1)-letter elements of the n-letter
+    // monoid.
+    let various_shorter_words = variants_on(shorter_words, n_letters);
+
+    let mut words = Vec::new();
+    for (left_word, left_sym) in various_shorter_words.iter() {
+        let left = chain(&[left_word, &[*left_sym]]);
+        for (right_word, right_sym) in various_shorter_words.iter() {
+            let right = chain(&[&[*right_sym], right_word]);
+            words.push(product(&left, &right, ConcatMode::MaxOverlap));
+        }
+    }
+
+    words
+}
+
+/// Generate all the members of the monoid over the first n letters
+/// of the alphabet, not just those using all the letters. Each
+/// element appears once, in its canonical (shortest) form.
+pub fn generate_monoid(n_letter: usize) -> Vec<Word> {
+    monoid_iter(n_letter).collect()
+}
+
+/// Like `generate_monoid`, but produces the elements on demand, in
+/// the same order. Each exact monoid is built once, from the one
+/// before, and only the one for the subset size currently being
+/// worked through is held in memory.
+pub fn monoid_iter(n_letter: usize) -> impl Iterator<Item = Word> {
+    let exact_monoids = (0..=n_letter).scan(None, |prev: &mut Option<Rc<Vec<Word>>>, i| {
+        let words = Rc::new(match prev.take() {
+            None => vec![vec![]],
+            Some(shorter_words) => next_exact_monoid(&shorter_words, i),
+        });
+        *prev = Some(words.clone());
+        Some((i, words))
+    });
+
+    // For each i letter subset of the alphabet...
+    exact_monoids.flat_map(move |(i, words)| {
+        (0..n_letter as Sym).combinations(i).flat_map(move |comb| {
+            // Create all the words using that subset:
+            let words = words.clone();
+            (0..words.len()).map(move |idx| {
+                words[idx]
+                    .iter()
+                    .map(|c| comb[*c as usize])
+                    .collect::<Word>()
+            })
+        })
+    })
+}
+
+/// Count the elements of the monoid over n letters by the length of
+/// their normal form: entry L is the number of elements of length L.
+pub fn length_histogram(n_letter: usize) -> Vec<u64> {
+    let mut counts = Vec::new();
+    for word in monoid_iter(n_letter) {
+        if counts.len() <= word.len() {
+            counts.resize(word.len() + 1, 0);
+        }
+        counts[word.len()] += 1;
+    }
+    counts
+}
+
+/// The length of the longest canonical word in the monoid over n
+/// letters.
+pub fn max_canonical_len(n_letter: usize) -> usize {
+    monoid_iter(n_letter)
+        .map(|word| word.len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Write the elements of the monoid over n letters, one per line, in
+/// the same order as `generate_monoid`. The empty word is written as
+/// `empty`. Output is buffered, so `out` needn't be.
+pub fn write_monoid<W: Write>(n_letter: usize, empty: &str, out: &mut W) -> io::Result<()> {
+    let mut out = BufWriter::new(out);
+    let mut line = Vec::new();
+    for word in monoid_iter(n_letter) {
+        line.clear();
+        if word.is_empty() {
+            line.extend(empty.as_bytes());
+        } else {
+            line.extend(word.iter().map(|sym| b'a' + sym));
+        }
+        line.push(b'\n');
+        out.write_all(&line)?;
+    }
+    out.flush()
+}
+
+/// The number of elements in the monoid over n letters, without
+/// generating them. The number of elements using exactly k letters is
+/// the product of (k - i + 1)^(2^i) for i in 1..=k, and the total is
+/// the sum of these over all subsets of the alphabet. Exact for up to
+/// 5 letters, but returns None from 6 letters on, as the count no
+/// longer fits in a u64. `estimate_output` gives the size beyond that.
+pub fn monoid_size(n_letter: usize) -> Option<u64> {
+    let mut total: u64 = 0;
+    for k in 0..=n_letter {
+        let mut exact: u64 = 1;
+        for i in 1..=k {
+            let exponent = 1u32.checked_shl(i as u32)?;
+            exact = exact.checked_mul(((k - i + 1) as u64).checked_pow(exponent)?)?;
+        }
+        total = total.checked_add(binomial(n_letter, k).checked_mul(exact)?)?;
+    }
+    Some(total)
+}
+
+/// Estimate the size of the monoid over n letters, and an upper
+/// bound on the bytes `write_monoid` would produce for it, without
+/// generating anything. Both are returned as base 10 logarithms, as
+/// they soon overflow even a f64: the monoid on 10 letters has about
+/// 10^449 elements. Uses the same formula as `monoid_size`, in
+/// floating point, so works (approximately) for monoids far too large
+/// to count exactly.
+pub fn estimate_output(n_letter: usize) -> (f64, f64) {
+    // Natural logs of the number of words, and bytes, using each
+    // number of letters.
+    let mut ln_elements = Vec::new();
+    let mut ln_bytes = Vec::new();
+    // Bound on the length of words using exactly k letters. Each is
+    // made by merging two words of (k-1) letters plus one extra
+    // letter.
+    let mut max_len = 0.0;
+    for k in 0..=n_letter {
+        if k > 0 {
+            max_len = if k == 1 { 1.0 } else { 2.0 * max_len + 2.0 };
+        }
+        let ln_exact = (1..=k)
+            .map(|i| 2f64.powi(i as i32) * ((k - i + 1) as f64).ln())
+            .sum::<f64>();
+        let ln_count = ln_binomial(n_letter, k) + ln_exact;
+        ln_elements.push(ln_count);
+        // The empty word is usually written as "ε", two bytes of
+        // UTF-8, plus a newline per word.
+        let len: f64 = if k == 0 { 2.0 } else { max_len };
+        ln_bytes.push(ln_count + (len + 1.0).ln());
+    }
+    (
+        ln_sum_exp(&ln_elements) / 10f64.ln(),
+        ln_sum_exp(&ln_bytes) / 10f64.ln(),
+    )
+}
+
+// The natural log of the sum of the numbers with the given natural
+// logs, without overflowing on the way.
+fn ln_sum_exp(lns: &[f64]) -> f64 {
+    let max = lns.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    max + lns.iter().map(|ln| (ln - max).exp()).sum::<f64>().ln()
+}
+
+// n choose k.
+fn binomial(n: usize, k: usize) -> u64 {
+    (0..k).fold(1, |acc, i| acc * (n - i) as u64 / (i + 1) as u64)
+}
+
+// The natural log of n choose k, for when it won't fit in a u64.
+fn ln_binomial(n: usize, k: usize) -> f64 {
+    (0..k).map(|i| ((n - i) as f64 / (i + 1) as f64).ln()).sum()
+}
+
+/// Embed words over the first n - 1 letters into the alphabet of n
+/// letters in every way that keeps the letters in order, i.e. by
+/// skipping one of the n letters. For each skipped letter i, in
+/// increasing order, every word is rewritten with each symbol >= i
+/// shifted up by one, and paired with i. So for n = 3, "ab" gives
+/// ("bc", a), ("ac", b) and ("ab", c).
+pub fn variants_on(words: &[Word], n_letters: usize) -> Vec<(Word, Sym)> {
+    let mut res = Vec::new();
+    for i in 0..n_letters as u8 {
+        for word in words.iter() {
+            let new_word = word
+                .iter()
+                .map(|sym| sym + u8::from(*sym >= i))
+                .collect::<Vec<_>>();
+            res.push((new_word, i));
+        }
+    }
+    res
+}
+
+/// The length of the longest suffix of `left` that is also a prefix
+/// of `right`. Zero if they don't overlap.
+// Runs KMP, matching `right` against `left`, so it's linear in the
+// lengths of the words rather than quadratic. The state at the end
+// of `left` is the longest prefix of `right` matched.
+pub fn overlap_len(left: WordRef, right: WordRef) -> usize {
+    count_search();
+    if right.is_empty() {
+        return 0;
+    }
+
+    let prefix = prefix_function(right);
+    let mut matched = 0;
+    for sym in left.iter() {
+        // A complete match can't be extended, so fall back first.
+        if matched == right.len() {
+            matched = prefix[matched - 1];
+        }
+        while matched > 0 && !sym_eq(right[matched], *sym) {
+            matched = prefix[matched - 1];
+        }
+        if sym_eq(right[matched], *sym) {
+            matched += 1;
+        }
+    }
+    matched
+}
+
+// The KMP prefix function: for each prefix of the word, the length of
+// the longest proper prefix that is also a suffix of it.
+fn prefix_function(word: WordRef) -> Vec<usize> {
+    let mut prefix = vec![0; word.len()];
+    let mut matched = 0;
+    for idx in 1..word.len() {
+        while matched > 0 && !sym_eq(word[idx], word[matched]) {
+            matched = prefix[matched - 1];
+        }
+        if sym_eq(word[idx], word[matched]) {
+            matched += 1;
+        }
+        prefix[idx] = matched;
+    }
+    prefix
+}
+
+/// Given two words that may overlap, generate the concatenation with
+/// maximal overlap. Words that don't overlap are just concatenated.
+pub fn merge(left: WordRef, right: WordRef) -> Word {
+    let overlap = overlap_len(left, right);
+    chain(&[left, &right[overlap..]])
+}
+
+/// Ways of concatenating two words. They all give the same element of
+/// the monoid, but different words for it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ConcatMode {
+    /// Plain concatenation, for when we want to see the whole product
+    /// being reduced.
+    Raw,
+    /// If the left word ends with the letter the right word starts
+    /// with, only keep one copy of it, so products never contain a
+    /// repeated letter where the words meet.
+    DropBoundary,
+    /// Only keep one copy of the longest suffix of the left word that
+    /// is also a prefix of the right word, as `merge` does.
+    MaxOverlap,
+}
+
+/// Concatenate two words, in the given way.
+pub fn product(lhs: WordRef, rhs: WordRef, mode: ConcatMode) -> Word {
+    match mode {
+        ConcatMode::Raw => chain(&[lhs, rhs]),
+        ConcatMode::DropBoundary if !lhs.is_empty() && lhs.last() == rhs.first() => {
+            chain(&[lhs, &rhs[1..]])
+        }
+        ConcatMode::DropBoundary => chain(&[lhs, rhs]),
+        ConcatMode::MaxOverlap => merge(lhs, rhs),
+    }
+}
@@ -0,0 +1,94 @@
+////////////////////////////////////////////////////////////////////////
+// Numbering the elements of the monoid over 3 letters, so they can be
+// used as array indices.
+//
+
+use std::sync::OnceLock;
+
+use crate::{generate_monoid, product, reduce, shortlex_cmp, ConcatMode, Sym, Word, WordRef};
+
+/// Number of letters in the monoid whose elements are indexed.
+pub const INDEXED_LETTERS: usize = 3;
+
+/// Number of elements in the indexed monoid.
+pub const NUM_INDEXED_ELEMENTS: usize = 160;
+
+// The elements of the monoid over 3 letters, sorted shortlex, built
+// on first use.
+fn indexed_elements() -> &'static [Word] {
+    static ELEMENTS: OnceLock<Vec<Word>> = OnceLock::new();
+    ELEMENTS.get_or_init(|| {
+        let mut words = generate_monoid(INDEXED_LETTERS);
+        words.sort_by(|a, b| shortlex_cmp(a, b));
+        assert_eq!(words.len(), NUM_INDEXED_ELEMENTS);
+        words
+    })
+}
+
+/// The index of a word among the elements of the monoid over 3
+/// letters, in shortlex order, so the empty word is 0 and "a" is 1.
+/// Returns None if the word isn't in normal form, or uses other
+/// letters.
+pub fn element_index(w: WordRef) -> Option<usize> {
+    indexed_elements()
+        .binary_search_by(|elt| shortlex_cmp(elt, w))
+        .ok()
+}
+
+/// The element of the monoid over 3 letters with the given index, as
+/// returned by `element_index`. Panics if the index is out of range.
+pub fn element_from_index(i: usize) -> Word {
+    indexed_elements()[i].clone()
+}
+
+/// The multiplication table of the monoid over n letters, with the
+/// elements numbered in shortlex order: entry [i][j] is the index of
+/// the product of elements i and j. For 3 letters, the numbering
+/// matches `element_index`. The table has a row and column per
+/// element, so wouldn't fit in memory for more letters: returns None
+/// for more than `INDEXED_LETTERS`.
+pub fn multiplication_table(n_letters: usize) -> Option<Vec<Vec<usize>>> {
+    if n_letters > INDEXED_LETTERS {
+        return None;
+    }
+
+    let mut elements = generate_monoid(n_letters);
+    elements.sort_by(|a, b| shortlex_cmp(a, b));
+    let index_of = |w: WordRef| {
+        // The elements should be closed under multiplication.
+        elements
+            .binary_search_by(|elt| shortlex_cmp(elt, w))
+            .expect("Product isn't an element")
+    };
+
+    // Reducing every product is slow, so just find the effect of
+    // multiplying on the right by each generator...
+    let times_gen = elements
+        .iter()
+        .map(|a| {
+            (0..n_letters as Sym)
+                .map(|g| index_of(reduce(&product(a, &[g], ConcatMode::Raw)).end()))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // and multiply by the letters of each element in turn.
+    let table = (0..elements.len())
+        .map(|i| {
+            elements
+                .iter()
+                .map(|b| b.iter().fold(i, |acc, g| times_gen[acc][*g as usize]))
+                .collect()
+        })
+        .collect();
+    Some(table)
+}
+
+/// Multiply two elements of the monoid over 3 letters, given by index
+/// as returned by `element_index`, returning the index of the
+/// product. The multiplication table is built on first use, so this
+/// is just a lookup after that.
+pub fn multiply_indexed(i: usize, j: usize) -> usize {
+    static TABLE: OnceLock<Vec<Vec<usize>>> = OnceLock::new();
+    TABLE.get_or_init(|| multiplication_table(INDEXED_LETTERS).unwrap())[i][j]
+}
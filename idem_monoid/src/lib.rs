@@ -0,0 +1,180 @@
+//
+// idem_monoid: Code to generate all the distinct words in an
+// idempotent monoid over n letters, normalise words to a canonical
+// form, and show the steps to perform that normalisation.
+//
+// Words are sequences of symbols, where symbol 0 is written 'a',
+// symbol 1 is 'b', and so on.
+//
+
+use std::cmp::Ordering;
+
+mod alphabet;
+mod checks;
+mod element;
+mod generate;
+mod index;
+mod profile;
+mod reduce;
+mod render;
+mod rle;
+mod rng;
+mod steps;
+mod structure;
+
+pub use alphabet::{Alphabet, InvalidLetter};
+pub use checks::{check_associativity, check_closure, check_idempotent, AssociativityFailure};
+pub use element::MonoidElement;
+pub use generate::{
+    estimate_output, generate_exact_monoid, generate_monoid, length_histogram, max_canonical_len,
+    merge, monoid_iter, monoid_size, overlap_len, product, variants_on, write_monoid, ConcatMode,
+};
+pub use index::{
+    element_from_index, element_index, multiplication_table, multiply_indexed, INDEXED_LETTERS,
+    NUM_INDEXED_ELEMENTS,
+};
+pub use profile::{take_overlap_profile, OverlapProfile, OVERLAP_PROFILING};
+pub use reduce::{
+    canonical, class_of, find_u, find_v, is_canonical, is_square_free, power, reduce, reduce_all,
+    reduce_minimal, reduce_tracked, reduce_tree, reduce_word, shortest_words, steps_between,
+    try_reduce, try_reduce_left, try_reduce_right, ReduceAll, ReduceAllExt, ReduceError,
+    ReduceTree, Reducer,
+};
+pub use rle::{compress_rle, expand_rle, parse_rle, word_to_rle, ParseError};
+pub use rng::random_words;
+#[cfg(feature = "serde")]
+pub use steps::SerializableSteps;
+pub use steps::{Op, Steps};
+pub use structure::{
+    cayley_graph, commuting_pairs, green_classes, is_identity, leq, special_elements, DClass,
+    SpecialElements,
+};
+
+////////////////////////////////////////////////////////////////////////
+// Types and utilities
+//
+
+/// A letter of the alphabet. Symbol 0 is 'a', 1 is 'b', etc.
+pub type Sym = u8;
+
+/// A word over the alphabet, as a sequence of symbols.
+pub type Word = Vec<Sym>;
+/// A borrowed word.
+pub type WordRef<'a> = &'a [Sym];
+
+/// Number of symbols that can be written as letters, 'a' to 'z'.
+pub const NUM_LETTERS: usize = 26;
+
+/// How the empty word is written by the command-line tools, unless
+/// told otherwise.
+pub const DEFAULT_EMPTY_SYMBOL: &str = "ε";
+
+/// Convert a symbol to its character, 'a' for 0, 'b' for 1, etc.
+pub fn sym_to_c(i: Sym) -> char {
+    char::from_digit(i as u32 + 10, 36).unwrap()
+}
+
+/// Convert a word to a string of lower-case letters.
+pub fn word_to_str(v: WordRef) -> String {
+    Alphabet::lowercase().encode(v)
+}
+
+/// Convert a string of lower-case letters to a word, 'a' being
+/// symbol 0. Panics on any other character.
+pub fn str_to_word(s: &str) -> Word {
+    try_str_to_word(s).unwrap()
+}
+
+/// Like `str_to_word`, but fails on characters that aren't
+/// lower-case letters, rather than panicking.
+pub fn try_str_to_word(s: &str) -> Result<Word, ReduceError> {
+    Alphabet::lowercase()
+        .decode(s)
+        .map_err(|e| ReduceError::InvalidChar(e.letter))
+}
+
+/// Convert a string to a word using the given alphabet, where
+/// `alphabet[i]` is written for symbol i. Fails with the first
+/// character that isn't in the alphabet.
+pub fn str_to_word_with(alphabet: &[char], s: &str) -> Result<Word, char> {
+    s.chars()
+        .map(|c| {
+            alphabet
+                .iter()
+                .position(|letter| *letter == c)
+                .map(|idx| idx as Sym)
+                .ok_or(c)
+        })
+        .collect()
+}
+
+/// Convert a word to a string using the given alphabet. Panics if a
+/// symbol is beyond the end of the alphabet.
+pub fn word_to_str_with(alphabet: &[char], v: WordRef) -> String {
+    v.iter().map(|sym| alphabet[*sym as usize]).collect()
+}
+
+/// The content of a word: the distinct letters it uses, in order.
+pub fn content(word: WordRef) -> Vec<Sym> {
+    let mut letters = word.to_vec();
+    letters.sort_unstable();
+    letters.dedup();
+    letters
+}
+
+/// A set of symbols, stored as a bitmask, for finding the content of
+/// a word without hashing. Has a bit for every `Sym`, so any word can
+/// be described.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ContentMask([u64; 4]);
+
+impl ContentMask {
+    /// The number of symbols the set can hold, from 0 up: one for
+    /// each `Sym`.
+    pub const CAPACITY: usize = Sym::MAX as usize + 1;
+
+    /// The empty set.
+    pub fn new() -> ContentMask {
+        ContentMask([0; 4])
+    }
+
+    /// The set of letters the word uses.
+    pub fn of(word: WordRef) -> ContentMask {
+        let mut mask = ContentMask::new();
+        for sym in word.iter() {
+            mask.insert(*sym);
+        }
+        mask
+    }
+
+    /// Add a symbol to the set.
+    pub fn insert(&mut self, sym: Sym) {
+        self.0[sym as usize / 64] |= 1 << (sym % 64);
+    }
+
+    /// Is the symbol in the set?
+    pub fn contains(&self, sym: Sym) -> bool {
+        self.0[sym as usize / 64] & (1 << (sym % 64)) != 0
+    }
+
+    /// The number of symbols in the set.
+    pub fn len(&self) -> usize {
+        self.0.iter().map(|bits| bits.count_ones() as usize).sum()
+    }
+
+    /// Is the set empty?
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|bits| *bits == 0)
+    }
+}
+
+/// Compare words in shortlex order: shorter words first, and words
+/// of the same length lexicographically.
+pub fn shortlex_cmp(a: WordRef, b: WordRef) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+/// Concatenate the given words.
+pub fn chain(words: &[WordRef]) -> Word {
+    words.iter().flat_map(|w| w.to_vec()).collect::<Vec<_>>()
+}
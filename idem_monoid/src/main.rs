@@ -1,492 +1,1334 @@
 //
-// idem_monoid: Code to generate all the distinct words in an
-// idempotent monoid over n letters, normalise words to a canonical
-// form, and show the steps to perform that normalisation.
+// idem_monoid: Command-line wrapper around the idem_monoid library,
+// to generate the elements of an idempotent monoid, or reduce a word
+// to its canonical form.
 //
 
-use clap::Parser;
-use itertools::Itertools;
+use clap::{ArgGroup, Parser, ValueEnum};
+use serde::Serialize;
+
+use idem_monoid::{
+    cayley_graph, check_associativity, check_closure, check_idempotent, commuting_pairs,
+    compress_rle, element_from_index, element_index, estimate_output, expand_rle, generate_monoid,
+    green_classes, is_canonical, is_square_free, length_histogram, leq, max_canonical_len,
+    monoid_iter, monoid_size, multiplication_table, multiply_indexed, power, product, random_words,
+    reduce, reduce_minimal, reduce_tree, reduce_word, shortest_words, shortlex_cmp,
+    special_elements, take_overlap_profile, write_monoid, Alphabet, ConcatMode, Reducer, Steps,
+    Word, WordRef, DEFAULT_EMPTY_SYMBOL, INDEXED_LETTERS, NUM_LETTERS, OVERLAP_PROFILING,
+};
 
 use std::collections::HashSet;
-use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process;
 
 ////////////////////////////////////////////////////////////////////////
-// Types and utilities
+// Main entry point.
 //
 
-type Sym = u8;
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum Format {
+    Text,
+    Latex,
+    Dot,
+    Json,
+    Markdown,
+}
 
-type Word = Vec<Sym>;
-type WordRef<'a> = &'a [Sym];
+// Formats for other tools to read the monoid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum ExportFormat {
+    Gap,
+}
 
-fn sym_to_c(i: Sym) -> char {
-    char::from_digit(i as u32 + 10, 36).unwrap()
+// A reduction, in a form that can be serialised.
+#[derive(Serialize)]
+struct ReductionReport {
+    input: String,
+    output: String,
+    num_steps: usize,
+    steps: Vec<StepReport>,
 }
 
-fn word_to_str(v: WordRef) -> String {
-    v.iter().map(|c| sym_to_c(*c)).collect::<String>()
+#[derive(Serialize)]
+struct StepReport {
+    before: String,
+    after: String,
 }
 
-fn str_to_word(s: &str) -> Word {
-    s.bytes().map(|c| c - b'a').collect()
+impl ReductionReport {
+    fn new(steps: &Steps, letters: &Letters) -> ReductionReport {
+        ReductionReport {
+            input: letters.to_str(steps.start()),
+            output: letters.to_str(steps.end()),
+            num_steps: steps.num_operations(),
+            steps: steps
+                .steps_with(&letters.alphabet)
+                .into_iter()
+                .map(|(before, after)| StepReport { before, after })
+                .collect(),
+        }
+    }
 }
 
-fn chain(words: &[WordRef]) -> Word {
-    words.iter().flat_map(|w| w.to_vec()).collect::<Vec<_>>()
+// A reduction of one word from stdin, written as a line of JSON.
+#[derive(Serialize)]
+struct BatchReport {
+    input: String,
+    output: String,
+    steps: usize,
 }
 
-////////////////////////////////////////////////////////////////////////
-// Monoid generation
-//
+// The characters used to write symbols 0, 1, 2, etc, and how to
+// write the empty word.
+#[derive(Clone, Debug)]
+struct Letters {
+    alphabet: Alphabet,
+    empty: String,
+}
 
-// Generate the idempotent monoid of all the words using *exactly* the
-// same n letters. e.g. "ab", "ba", "aba", "bab", but not "a" and "b".
-fn generate_exact_monoid(n_letters: usize) -> Vec<Word> {
-    if n_letters == 0 {
-        return vec![vec![]];
+impl Letters {
+    // Parse the --alphabet argument.
+    fn parse(s: &str) -> Result<Letters, String> {
+        let letters = s.chars().collect::<Vec<_>>();
+        if letters.is_empty() || letters.len() > NUM_LETTERS {
+            return Err(format!("need between 1 and {} letters", NUM_LETTERS));
+        }
+        for (idx, c) in letters.iter().enumerate() {
+            if letters[..idx].contains(c) {
+                return Err(format!("'{}' appears more than once", c));
+            }
+            // These have other meanings in our input and output.
+            if c.is_whitespace() || c.is_ascii_digit() || [',', '(', ')'].contains(c) {
+                return Err(format!("'{}' can't be used as a letter", c));
+            }
+        }
+        Ok(Letters {
+            alphabet: Alphabet::new(letters),
+            empty: String::from(DEFAULT_EMPTY_SYMBOL),
+        })
     }
 
-    // Start with the words from the (n-1)-letter monoid.
-    let shorter_words = generate_exact_monoid(n_letters - 1);
+    // Convert a string to a word, describing the problem if it's not
+    // valid.
+    fn to_word(&self, s: &str) -> Result<Word, String> {
+        if s == self.empty {
+            return Ok(Word::new());
+        }
+        self.alphabet.decode(s).map_err(|e| {
+            let chars = self.alphabet.letters();
+            let first = chars[0];
+            let last = chars[chars.len() - 1];
+            let contiguous = (first..=last).eq(chars.iter().copied());
+            if contiguous && chars.len() > 1 {
+                format!(
+                    "invalid character '{}', expected '{}' to '{}'",
+                    e.letter, first, last
+                )
+            } else {
+                format!(
+                    "invalid character '{}', expected one of '{}'",
+                    e.letter,
+                    chars.iter().collect::<String>()
+                )
+            }
+        })
+    }
 
-    // Generate all the variants on this (n-1)-letter monoid by using
-    // all but one of the letter from the n-letter alphabet,
-    // generateing all the (n-1)-letter elements of the n-letter
-    // monoid.
-    let various_shorter_words = variants_on(&shorter_words, n_letters);
+    // Convert a word to a string.
+    fn to_str(&self, word: WordRef) -> String {
+        self.alphabet.encode(word)
+    }
 
-    let mut words = Vec::new();
-    for (left_word, left_sym) in various_shorter_words.iter() {
-        let left = chain(&[left_word, &[*left_sym]]);
-        for (right_word, right_sym) in various_shorter_words.iter() {
-            let right = chain(&[&[*right_sym], right_word]);
-            words.push(merge(&left, &right));
+    // Convert a word to a string, showing the empty word with the
+    // empty symbol.
+    fn display(&self, word: WordRef) -> String {
+        if word.is_empty() {
+            self.empty.clone()
+        } else {
+            self.to_str(word)
         }
     }
 
-    words
-}
+    // Are these the usual 'a' to 'z'?
+    fn is_default(&self) -> bool {
+        self.alphabet == Alphabet::lowercase()
+    }
 
-// Generate all the members of the monoid, not just those using all
-// possible letters.
-fn generate_monoid(n_letter: usize) -> Vec<Word> {
-    let mut res = Vec::new();
-
-    // For each i letter subset of the alphabet...
-    for i in 0..=n_letter {
-        let words = generate_exact_monoid(i);
-        for comb in (0..n_letter as Sym).combinations(i) {
-            // Create all the words using that subset:
-            for word in words.iter() {
-                res.push(word.iter().map(|c| comb[*c as usize]).collect::<Word>());
-            }
+    // Set the symbol for the empty word, which mustn't be readable as
+    // any other word.
+    fn set_empty(&mut self, empty: &str) -> Result<(), String> {
+        if empty.chars().any(|c| c.is_whitespace() || c == ',') {
+            return Err(format!("'{}' can't be used for the empty word", empty));
         }
+        if !empty.is_empty() && self.alphabet.decode(empty).is_ok() {
+            return Err(format!(
+                "'{}' is already a word, so can't be used for the empty word",
+                empty
+            ));
+        }
+        self.empty = empty.to_string();
+        Ok(())
     }
 
-    res
-}
-
-// Given a set of words, generate the set of words with one more
-// letter, and the associated missed-out letter.
-fn variants_on(words: &[Word], n_letters: usize) -> Vec<(Word, Sym)> {
-    let mut res = Vec::new();
-    for i in 0..n_letters as u8 {
-        for word in words.iter() {
-            let new_word = word
-                .iter()
-                .map(|sym| sym + u8::from(*sym >= i))
-                .collect::<Vec<_>>();
-            res.push((new_word, i));
+    // Exit with a message if there aren't enough letters for the
+    // generators.
+    fn check_size(&self, n_letters: usize) {
+        if n_letters > self.alphabet.letters().len() {
+            eprintln!(
+                "Alphabet only has {} letters, but {} generators were requested",
+                self.alphabet.letters().len(),
+                n_letters
+            );
+            process::exit(1);
         }
     }
-    res
 }
 
-// Given two words that may overlap, generate the concatenation with
-// maximal overlap.
-fn merge(left: WordRef, right: WordRef) -> Word {
-    let l_len = left.len();
-    let r_len = right.len();
+#[derive(Debug, Parser)]
+#[clap(name = "idem_monoid")]
+#[clap(about = "Tool for generating and reducing elements of an idempotent free monoid", long_about = None)]
+#[clap(group(ArgGroup::new("mode").multiple(false)))]
+struct Cli {
+    /// Size of alphabet to use when generating the idempotent monoid.
+    #[clap(long, value_parser, default_value_t = 3)]
+    generators: usize,
 
-    let start = if r_len > l_len { 0 } else { l_len - r_len };
+    /// Or a word, or comma-separated words, to reduce to canonical form
+    #[clap(long, value_parser, group = "mode")]
+    reduce: Option<String>,
 
-    for idx in start..=l_len {
-        let l_part = &left[idx..];
-        let r_part = &right[..l_part.len()];
-        if l_part == r_part {
-            return chain(&[&left[..idx], right]);
-        }
-    }
+    /// Or reduce each line read from stdin. With --format json, each
+    /// result is written as a line of JSON as soon as it's found
+    #[clap(long, value_parser, group = "mode")]
+    reduce_stdin: bool,
+
+    /// Or reduce words interactively, with commands to change settings
+    #[clap(long, value_parser, group = "mode")]
+    repl: bool,
+
+    /// Or a word, or comma-separated words, to check are already in
+    /// canonical form
+    #[clap(long, value_parser, group = "mode")]
+    is_canonical: Option<String>,
+
+    /// Or a word and a count, e.g. aba,4, to show the power of the word
+    /// reduces to the word itself
+    #[clap(long, value_parser, group = "mode")]
+    power: Option<String>,
+
+    /// Or two comma-separated words to multiply, reducing the product
+    #[clap(long, value_parser, group = "mode")]
+    multiply: Option<String>,
+
+    /// Or print the multiplication table, with the elements numbered
+    /// in shortlex order, as listed by --sorted. Up to 3 generators
+    #[clap(long, value_parser, group = "mode")]
+    table_indexed: bool,
+
+    /// Or write the multiplication table for another tool to read. For
+    /// GAP, it's passed to MagmaByMultiplicationTable
+    #[clap(long, value_enum, group = "mode")]
+    export: Option<ExportFormat>,
+
+    /// Or two comma-separated words to check for equivalence
+    #[clap(long, value_parser, group = "mode")]
+    equivalent: Option<String>,
+
+    /// Or two comma-separated words, to show where their normal forms
+    /// first differ
+    #[clap(long, value_parser, group = "mode")]
+    first_difference: Option<String>,
+
+    /// Or two comma-separated words a,b to check a <= b in the natural
+    /// order, i.e. ab = ba = a
+    #[clap(long, value_parser, group = "mode")]
+    leq: Option<String>,
+
+    /// Or just print the number of elements in the monoid
+    #[clap(long, value_parser, group = "mode")]
+    size: bool,
+
+    /// Or generate the monoid, and fail unless it has exactly this many
+    /// distinct elements
+    #[clap(long, value_parser, group = "mode")]
+    assert_size: Option<usize>,
+
+    /// Or estimate the number of elements and size of the output
+    #[clap(long, value_parser, group = "mode")]
+    estimate: bool,
+
+    /// Or count the elements of each length
+    #[clap(long, value_parser, group = "mode")]
+    histogram_of_lengths: bool,
+
+    /// Or print the longest elements, whose length is the most a
+    /// canonical word can need
+    #[clap(long, value_parser, group = "mode")]
+    longest: bool,
+
+    /// Or print the right Cayley graph of the monoid
+    #[clap(long, value_parser, group = "mode")]
+    cayley: bool,
+
+    /// Or report the identity and any left or right zeros
+    #[clap(long, value_parser, group = "mode")]
+    structure: bool,
+
+    /// Or list the pairs of elements, other than the identity, that
+    /// commute
+    #[clap(long, value_parser, group = "mode")]
+    commuting: bool,
+
+    /// Or show Green's relations, drawing each D-class as a grid with a
+    /// row per R-class and a column per L-class
+    #[clap(long, value_parser, group = "mode")]
+    green: bool,
+
+    /// Or check the generated elements are closed under multiplication
+    #[clap(long, value_parser, group = "mode")]
+    check_closure: bool,
+
+    /// Or check multiplication of the generated elements is associative
+    #[clap(long, value_parser, group = "mode")]
+    check_associativity: bool,
+
+    /// Or check every generated element is idempotent
+    #[clap(long, value_parser, group = "mode")]
+    check_idempotent: bool,
+
+    /// When checking associativity, only check this many random triples
+    #[clap(long, value_parser)]
+    sample: Option<usize>,
 
-    panic!("Should always equal at zero length overlap!");
-}
+    /// Or generate this many random words over the generators
+    #[clap(long, value_parser, group = "mode")]
+    random: Option<usize>,
 
-////////////////////////////////////////////////////////////////////////
-// Word reduction
-//
+    /// Length of the random words
+    #[clap(long, value_parser, default_value_t = 10)]
+    length: usize,
 
-// Find the length of the longest left subword using n distinct letters.
-fn find_left_subword(word: WordRef, n: usize) -> usize {
-    let mut letters = HashSet::new();
-    for (idx, sym) in word.iter().enumerate() {
-        letters.insert(*sym);
-        if letters.len() == n + 1 {
-            return idx;
-        }
-    }
-    panic!("Oh dear, not enough distinct letters (shouldn't happen!)");
-}
+    /// Seed for the random words, which fully determines them
+    #[clap(long, value_parser, default_value_t = 0)]
+    seed: u64,
 
-// Find the index of the start of the longest right subword using n
-// distinct letters.
-fn find_right_subword(word: WordRef, n: usize) -> usize {
-    let mut letters = HashSet::new();
-    for (idx, sym) in word.iter().enumerate().rev() {
-        letters.insert(*sym);
-        if letters.len() == n + 1 {
-            return idx + 1;
-        }
-    }
-    panic!("Oh dear, not enough distinct letters (shouldn't happen!)");
-}
+    /// With --random, also print each word's normal form
+    #[clap(long, value_parser)]
+    reduced: bool,
 
-// Reduce the left sub-word that uses all but one of the characters in
-// the word.
-fn reduce_left(word: WordRef, n_letters: usize) -> Steps {
-    let len = find_left_subword(word, n_letters - 1);
-    let to_reduce = &word[..len];
-    let rest = &word[len..];
-    reduce(to_reduce).suffix(&[rest])
-}
+    /// When generating, sort the elements shortlex?
+    #[clap(long, value_parser)]
+    sorted: bool,
 
-// Same, but for the right.
-fn reduce_right(word: WordRef, n_letters: usize) -> Steps {
-    let len = find_right_subword(word, n_letters - 1);
-    let to_reduce = &word[len..];
-    let rest = word[..len].to_vec();
-    Steps::prefix(&[&rest], &reduce(to_reduce))
-}
+    /// When generating, drop any repeated elements?
+    #[clap(long, value_parser)]
+    distinct: bool,
 
-// Like `merge`, but returns steps. Finds the unsquaring the maximally
-// shortens the word.
-fn reduce_middle(left: WordRef, right: WordRef) -> Steps {
-    let l_len = left.len();
-    let r_len = right.len();
+    /// When generating, only print square-free elements
+    #[clap(long, value_parser)]
+    square_free: bool,
 
-    // Starting index of the biggest possible overlap.
-    let start = if r_len > l_len { 0 } else { l_len - r_len };
+    /// When generating, only print elements of this length
+    #[clap(long, value_parser)]
+    only_length: Option<usize>,
 
-    for idx in start..l_len {
-        // Get the left and right potential parts of the overlap, see
-        // if they do.
-        let l_part = &left[idx..];
-        let r_part = &right[..l_part.len()];
-        if l_part == r_part {
-            // They do. Build the unsquaring operation to eliminate
-            // it.
-            let l = &left[..idx];
-            let m = l_part;
-            let r = &right[l_part.len()..];
-            return Steps::prefix(&[l], &Steps::square(&[m]).suffix(&[r])).time_rev();
-        }
-    }
+    /// When generating, only print elements at least this long
+    #[clap(long, value_parser)]
+    min_length: Option<usize>,
 
-    Steps::empty(&chain(&[left, right]))
-}
+    /// When generating, only print elements at most this long
+    #[clap(long, value_parser)]
+    max_length: Option<usize>,
 
-// Given a word, produces the steps that maximally shortens it to
-// normal form.
-fn reduce(word: WordRef) -> Steps {
-    // Base case - do nothing for empty string.
-    if word.is_empty() {
-        return Steps::empty(word);
-    }
+    /// If reducing a word, find a derivation with the fewest steps,
+    /// through words at most this long
+    #[clap(long, value_parser)]
+    minimal: Option<usize>,
 
-    // Get alphabet size.
-    let letters: HashSet<u8> = HashSet::from_iter(word.iter().copied());
-    let n_letters = letters.len();
+    /// If reducing a word, read it run-length encoded, e.g. a4b4
+    #[clap(long, value_parser)]
+    rle: bool,
 
-    // Place to accumulate the steps performed:
-    let mut steps = Vec::new();
+    /// If reducing a word, write the result run-length encoded
+    #[clap(long, value_parser)]
+    rle_out: bool,
 
-    // Reduce the subwords (using n - 1 letters) on the left and right.
-    steps.push(reduce_left(word, n_letters));
-    let word = &steps.last().unwrap().end;
-    steps.push(reduce_right(word, n_letters));
-    let word = &steps.last().unwrap().end;
+    /// If reducing a word, reduce its mirror image instead?
+    #[clap(long, value_parser)]
+    reverse: bool,
 
-    // Extract the left and right shortest words using all the letters
-    // (one longer than the longest words using all but one letter!).
-    let l_len = find_left_subword(word, n_letters - 1) + 1;
-    let l_word = word[..l_len].to_vec();
+    /// When generating, write the elements to this file, not stdout
+    #[clap(long, value_parser)]
+    output: Option<PathBuf>,
 
-    let r_idx = find_right_subword(word, n_letters - 1) - 1;
-    let r_word = word[r_idx..].to_vec();
+    /// If reducing a word, show the reduction path?
+    #[clap(long, value_parser)]
+    verbose: bool,
 
-    // If the left and right subwords overlap no further reduction is
-    // possible, they're already in minimal form.
-    if l_len <= r_idx {
-        // Only try to remove a middle section if there is one.
-        if l_len < r_idx {
-            steps.push(remove_middle(&l_word, &word[l_len..r_idx], &r_word));
-        }
+    /// If showing the reduction path, highlight the factor operated on
+    /// in each step? Ignored unless writing to a terminal
+    #[clap(long, value_parser)]
+    color: bool,
 
-        // Then remove overlap between left and right subwords.
-        steps.push(reduce_middle(&l_word, &r_word));
-    }
-    Steps::join(steps)
-}
+    /// If reducing a word, print to stderr how much work finding
+    /// overlaps took. Needs the "profile" feature
+    #[clap(long, value_parser)]
+    profile_overlap: bool,
 
-////////////////////////////////////////////////////////////////////////
-// Structure to represent a sequence of squaring/unsquaring
-// steps. Intended to make it impossible (when using the interface) to
-// generate invalid sequences of operations.
-//
+    /// If reducing a word, explain each step in words?
+    #[clap(long, value_parser)]
+    explain: bool,
 
-// A sequence of steps to go from a word to another representation of
-// it. It tries to encapsulate the steps to make sure we don't
-// accidentally mis-step.
-struct Steps {
-    start: Word,
-    end: Word,
-    // We use strings to allow us to make the steps clearer.  Each
-    // step represents before and after the step, so that the after of
-    // one step should be the same as the before of the next.
-    steps: Vec<(String, String)>,
+    /// If reducing a word, drop pairs of steps that cancel out?
+    #[clap(long, value_parser)]
+    simplify: bool,
+
+    /// If reducing a word, show how it's reduced recursively?
+    #[clap(long, value_parser)]
+    tree: bool,
+
+    /// If reducing a word, count the operations performed?
+    #[clap(long, value_parser)]
+    count_steps: bool,
+
+    /// If reducing a word, list all the shortest words in its class?
+    #[clap(long, value_parser)]
+    show_class: bool,
+
+    /// Letters to read and write words with, instead of 'a' to 'z'. Not
+    /// used for LaTeX, DOT or Markdown output
+    #[clap(long, value_parser = Letters::parse, default_value = "abcdefghijklmnopqrstuvwxyz")]
+    alphabet: Letters,
+
+    /// How to write the empty word, which is also accepted as input
+    #[clap(long, value_parser, default_value = DEFAULT_EMPTY_SYMBOL)]
+    empty_symbol: String,
+
+    /// Output format for the reduction path or generated elements
+    #[clap(long, value_enum, default_value_t = Format::Text)]
+    format: Format,
 }
 
-impl fmt::Display for Steps {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        for step in self.steps.iter() {
-            writeln!(f, "{} -> {}", step.0, step.1)?;
-        }
-        Ok(())
-    }
+// Parse a word from the command line, exiting with a message if it's
+// not valid.
+fn parse_word(s: &str, letters: &Letters) -> Word {
+    letters.to_word(s).unwrap_or_else(|e| {
+        eprintln!("Invalid word '{}': {}", s, e);
+        process::exit(1);
+    })
 }
 
-impl Steps {
-    // No-op
-    fn empty(w: WordRef) -> Steps {
-        Steps {
-            start: w.to_vec(),
-            end: w.to_vec(),
-            steps: Vec::new(),
+// Parse a comma-separated pair of words.
+fn parse_pair(s: &str, letters: &Letters) -> (Word, Word) {
+    match s.split_once(',') {
+        Some((l, r)) => (parse_word(l, letters), parse_word(r, letters)),
+        None => {
+            eprintln!("Expected two comma-separated words, got '{}'", s);
+            process::exit(1);
         }
     }
+}
 
-    // Represents a step from w to ww:
-    fn square(m: &[WordRef]) -> Steps {
-        let mw = chain(m);
-        let m2w = chain(&[&mw, &mw]);
+// Show the steps of a reduction as text, coloured if asked for and
+// writing to a terminal.
+fn steps_text(steps: &Steps, letters: &Letters, color: bool) -> String {
+    if color && io::stdout().is_terminal() {
+        steps.to_ansi_with(&letters.alphabet)
+    } else {
+        steps.to_string_with(&letters.alphabet)
+    }
+}
 
-        let m1s = word_to_str(&mw);
-        let m2s = word_to_str(&m2w);
+// Write a count of things, e.g. "1 square" or "2 squares".
+fn count(n: usize, thing: &str) -> String {
+    format!("{} {}{}", n, thing, if n == 1 { "" } else { "s" })
+}
 
-        Steps {
-            start: mw,
-            end: m2w,
-            steps: vec![(format!("({m1s})"), format!("({m2s})"))],
+// Print the result of a reduction, and optionally how we got there.
+fn print_reduction(steps: &Steps, args: &Cli) {
+    match args.format {
+        Format::Latex => {
+            print!("{}", steps.to_latex());
+            return;
+        }
+        Format::Dot => {
+            print!("{}", steps.to_dot());
+            return;
+        }
+        Format::Markdown => {
+            print!("{}", steps.to_markdown());
+            return;
         }
+        Format::Json => {
+            println!(
+                "{}",
+                serde_json::to_string(&ReductionReport::new(steps, &args.alphabet)).unwrap()
+            );
+            return;
+        }
+        Format::Text => (),
+    }
+    if args.verbose {
+        println!("{}", steps_text(steps, &args.alphabet, args.color));
+        if steps.peaked() {
+            println!(
+                "Note: the word grows to {} letters before it shrinks",
+                steps.max_intermediate_len()
+            );
+        }
+    }
+    if args.explain {
+        println!("{}", steps.explain_with(&args.alphabet.alphabet));
     }
+    print_end_word(steps.end(), args);
+    if args.count_steps {
+        println!(
+            "{} ({}, {})",
+            count(steps.num_operations(), "operation"),
+            count(steps.num_squares(), "square"),
+            count(steps.num_unsquares(), "unsquare")
+        );
+        println!(
+            "Longest intermediate word: {}",
+            count(steps.max_intermediate_len(), "letter")
+        );
+    }
+}
 
-    fn join(list: Vec<Steps>) -> Steps {
-        let start = list.first().unwrap().start.clone();
-        let mut end = start.clone();
-        let mut steps = Vec::new();
+// Print the word a reduction ends at, as the last part of
+// 'print_reduction'.
+fn print_end_word(word: WordRef, args: &Cli) {
+    let mut as_str = args.alphabet.display(word);
+    if args.rle_out {
+        as_str = compress_rle(&as_str);
+    }
+    println!("{}", as_str);
+}
 
-        for mut step in list.into_iter() {
-            assert_eq!(end, step.start);
-            steps.append(&mut step.steps);
-            end = step.end;
-        }
+// Does 'print_reduction' only print the word the reduction ends at,
+// so there's no need to find the steps? Anything added to
+// 'print_reduction' that uses the steps needs adding here.
+fn only_prints_end_word(args: &Cli) -> bool {
+    args.format == Format::Text && !args.verbose && !args.explain && !args.count_steps
+}
 
-        Steps { start, end, steps }
+// Print two reductions next to each other.
+fn print_side_by_side(l: &Steps, r: &Steps, letters: &Letters) {
+    let l_lines = l
+        .to_string_with(&letters.alphabet)
+        .lines()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    let r_lines = r
+        .to_string_with(&letters.alphabet)
+        .lines()
+        .map(String::from)
+        .collect::<Vec<_>>();
+    let width = l_lines.iter().map(|s| s.chars().count()).max().unwrap_or(0);
+    for idx in 0..l_lines.len().max(r_lines.len()) {
+        let l_line = l_lines.get(idx).map_or("", |s| s.as_str());
+        let r_line = r_lines.get(idx).map_or("", |s| s.as_str());
+        println!("{:width$} | {}", l_line, r_line, width = width);
     }
+    println!();
+}
 
-    // Written this way so we can use it in prefix form
-    fn prefix(words: &[WordRef], s: &Steps) -> Steps {
-        let word = chain(words);
-        let str = word_to_str(&word);
-        Steps {
-            start: chain(&[&word, &s.start]),
-            end: chain(&[&word, &s.end]),
-            steps: s
-                .steps
-                .iter()
-                .map(|(l, r)| (format!("{}{}", str, l), format!("{}{}", str, r)))
-                .collect::<Vec<_>>(),
-        }
+// The elements to print in generation mode, in the requested order.
+fn generated_words(args: &Cli) -> Box<dyn Iterator<Item = Word>> {
+    let (only, min, max) = (args.only_length, args.min_length, args.max_length);
+    let square_free = args.square_free;
+    let words = monoid_iter(args.generators).filter(move |word| {
+        (!square_free || is_square_free(word))
+            && only.is_none_or(|len| word.len() == len)
+            && min.is_none_or(|len| word.len() >= len)
+            && max.is_none_or(|len| word.len() <= len)
+    });
+    if !args.sorted && !args.distinct {
+        return Box::new(words);
     }
 
-    fn suffix(&self, words: &[WordRef]) -> Steps {
-        let word = chain(words);
-        let str = word_to_str(&word);
-        Steps {
-            start: chain(&[&self.start, &word]),
-            end: chain(&[&self.end, &word]),
-            steps: self
-                .steps
-                .iter()
-                .map(|(l, r)| (format!("{}{}", l, str), format!("{}{}", r, str)))
-                .collect::<Vec<_>>(),
-        }
+    let mut words = words.collect::<Vec<_>>();
+    if args.distinct {
+        // Keep the first occurrence of each word.
+        let before = words.len();
+        let mut seen = HashSet::new();
+        words.retain(|word| seen.insert(word.clone()));
+        eprintln!("Removed {} duplicates", before - words.len());
     }
+    if args.sorted {
+        words.sort_by(|a, b| shortlex_cmp(a, b));
+    }
+    Box::new(words.into_iter())
+}
 
-    // Generate steps for the reverse operation.
-    fn time_rev(&self) -> Steps {
-        Steps {
-            start: self.end.clone(),
-            end: self.start.clone(),
-            steps: self
-                .steps
-                .iter()
-                .rev()
-                .map(|(l, r)| (r.clone(), l.clone()))
-                .collect(),
+// Write out the elements in generation mode.
+fn write_generated<W: Write>(args: &Cli, out: &mut W) -> io::Result<()> {
+    if args.format == Format::Json {
+        // Stream out a JSON array of the elements.
+        let mut out = BufWriter::new(out);
+        write!(out, "[")?;
+        for (idx, word) in generated_words(args).enumerate() {
+            let sep = if idx == 0 { "" } else { "," };
+            write!(
+                out,
+                "{}{}",
+                sep,
+                serde_json::to_string(&args.alphabet.to_str(&word)).unwrap()
+            )?;
+        }
+        writeln!(out, "]")?;
+        out.flush()
+    } else if args.sorted
+        || args.distinct
+        || args.square_free
+        || args.only_length.is_some()
+        || args.min_length.is_some()
+        || args.max_length.is_some()
+        || !args.alphabet.is_default()
+    {
+        let mut out = BufWriter::new(out);
+        for word in generated_words(args) {
+            writeln!(out, "{}", args.alphabet.display(&word))?;
         }
+        out.flush()
+    } else {
+        write_monoid(args.generators, &args.alphabet.empty, out)
     }
+}
 
-    // Generate steps for the word written backwards.
-    fn word_rev(&self) -> Steps {
-        fn backwards(s: &str) -> String {
-            s.chars()
-                .rev()
-                .map(|c| match c {
-                    ')' => '(',
-                    '(' => ')',
-                    _ => c,
-                })
-                .collect::<String>()
+// Reduce a word given on the command line, printing the result in
+// the requested way. Returns false if the word isn't valid.
+fn reduce_and_print(reduce_me: &str, args: &Cli) -> bool {
+    let expanded = if args.rle {
+        expand_rle(reduce_me)
+    } else {
+        Ok(reduce_me.to_string())
+    };
+    let mut word = match expanded
+        .map_err(|e| e.to_string())
+        .and_then(|s| args.alphabet.to_word(&s))
+    {
+        Ok(word) => word,
+        Err(e) => {
+            eprintln!("Can't reduce '{}': {}", reduce_me, e);
+            return false;
         }
-
-        Steps {
-            start: self.start.iter().rev().copied().collect::<Vec<_>>(),
-            end: self.end.iter().rev().copied().collect::<Vec<_>>(),
-            steps: self
-                .steps
-                .iter()
-                .map(|(l, r)| (backwards(l), backwards(r)))
-                .collect::<Vec<_>>(),
+    };
+    if args.reverse {
+        word.reverse();
+    }
+    // Only count the work for this word.
+    take_overlap_profile();
+    let steps = match args.minimal {
+        Some(max_len) => match reduce_minimal(&word, max_len) {
+            Some(steps) => steps,
+            None => {
+                eprintln!(
+                    "Can't reduce '{}' through words of at most {} letters",
+                    reduce_me, max_len
+                );
+                return false;
+            }
+        },
+        None => reduce(&word),
+    };
+    let steps = if args.simplify {
+        steps.simplify()
+    } else {
+        steps
+    };
+    if args.profile_overlap {
+        let profile = take_overlap_profile();
+        eprintln!(
+            "{}: {} overlap searches, {} symbol comparisons",
+            reduce_me, profile.searches, profile.comparisons
+        );
+    }
+    if args.tree && args.format == Format::Text {
+        print!(
+            "{}",
+            reduce_tree(&word).to_string_with(&args.alphabet.alphabet)
+        );
+    }
+    print_reduction(&steps, args);
+    if args.reverse && args.verbose && args.format == Format::Text {
+        // Show the mirror image of reducing the original word, to
+        // compare.
+        word.reverse();
+        let mirrored = reduce(&word).word_rev();
+        println!("Mirror image of reducing {}:", reduce_me);
+        println!("{}", steps_text(&mirrored, &args.alphabet, args.color));
+        println!("{}", args.alphabet.display(mirrored.end()));
+    }
+    if args.show_class {
+        let words = shortest_words(steps.end());
+        println!("Shortest words in class ({}):", words.len());
+        for word in words.iter() {
+            println!("  {}", args.alphabet.display(word));
         }
     }
+    true
 }
 
-////////////////////////////////////////////////////////////////////////
-// Core reduction algorithm, from Lothaire.
-//
-
-// Given x, y, alph(y) <= alph(x), find u s.t. x ~ xyu, and the steps
-// to go from x to xyu.
-fn find_u(x: WordRef, y: WordRef) -> (Steps, Word) {
-    // Keep squaring appropriate subwords to build up a word of the
-    // form xyu. 'l' holds the word left of the insertion point, 'r'
-    // the word to the right.
-    let mut l = x.to_vec();
-    let mut r: Word = Vec::new();
-
-    let mut steps = Vec::new();
-
-    for sym in y.iter() {
-        let (repeat_point, _) = l
+// Write the multiplication table as GAP input. GAP numbers elements
+// from 1, and the elements are listed in a comment so the numbers can
+// be matched up.
+fn write_gap(args: &Cli) {
+    let mut elements = generate_monoid(args.generators);
+    elements.sort_by(|a, b| shortlex_cmp(a, b));
+    println!(
+        "# The free idempotent monoid on {} generators.",
+        args.generators
+    );
+    println!("# Elements, numbered in shortlex order:");
+    for (idx, word) in elements.iter().enumerate() {
+        println!("#   {}: {}", idx + 1, args.alphabet.display(word));
+    }
+    println!("M := MagmaByMultiplicationTable([");
+    let table = indexed_table(args.generators);
+    for (idx, row) in table.iter().enumerate() {
+        let row = row
             .iter()
-            .enumerate()
-            .rev()
-            .find(|(_, sym2)| **sym2 == *sym)
-            .unwrap();
-
-        steps.push(
-            Steps::prefix(&[&l[..repeat_point]], &Steps::square(&[&l[repeat_point..]]))
-                .suffix(&[&r]),
+            .map(|idx| (idx + 1).to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sep = if idx + 1 < table.len() { "," } else { "" };
+        println!("  [{}]{}", row, sep);
+    }
+    println!("]);");
+}
+
+// The multiplication table, or an error if there are too many
+// generators for it to fit in memory.
+fn indexed_table(n_letters: usize) -> Vec<Vec<usize>> {
+    multiplication_table(n_letters).unwrap_or_else(|| {
+        eprintln!(
+            "The multiplication table is only available for up to {} generators, not {}",
+            INDEXED_LETTERS, n_letters
         );
+        process::exit(1);
+    })
+}
 
-        r = chain(&[&l[repeat_point + 1..], &r]);
-        l.push(*sym);
+// Write a number given as its base 10 logarithm in scientific
+// notation, e.g. 2.726e26, however big it is.
+fn format_log10(log: f64) -> String {
+    let exponent = log.floor();
+    let mantissa = 10f64.powf(log - exponent);
+    // Rounding the mantissa can carry into the exponent.
+    if format!("{:.3}", mantissa) == "10.000" {
+        format!("1.000e{}", exponent + 1.0)
+    } else {
+        format!("{:.3}e{}", mantissa, exponent)
     }
+}
 
-    (Steps::join(steps), r)
+// Most elements for the modes that multiply every element by every
+// other, or by each generator, to work through. There are 160
+// elements on 3 generators, but 332381 on 4, which takes far too long.
+const MAX_SMALL_MONOID: u64 = 10_000;
+
+// Exit with a message if the monoid is too big for one of those modes
+// to finish. 'or' suggests anything else to try.
+fn check_small_monoid(n_letters: usize, or: &str) {
+    let small_enough = |n: usize| monoid_size(n).is_some_and(|size| size <= MAX_SMALL_MONOID);
+    if small_enough(n_letters) {
+        return;
+    }
+    let size = monoid_size(n_letters).map_or_else(|| String::from("too many"), |s| s.to_string());
+    let max_letters = (0..).take_while(|n| small_enough(*n)).last().unwrap();
+    eprintln!(
+        "The monoid on {} generators has {} elements, which is too many. \
+         Try at most {} generators{}.",
+        n_letters, size, max_letters, or
+    );
+    process::exit(1);
 }
 
-// Given x, y, alph(y) <= alph(x), find v s.t. x ~ vyx
-fn find_v(x: WordRef, y: WordRef) -> (Steps, Word) {
-    let mut xr = x.to_vec();
-    xr.reverse();
-    let mut yr = y.to_vec();
-    yr.reverse();
-    let (steps, mut ur) = find_u(&xr, &yr);
-    ur.reverse();
-    (steps.word_rev(), ur)
+// Reduce each line of stdin, printing the results in order. Returns
+// false if any line couldn't be reduced. For JSON, each result is a
+// separate object on its own line, flushed straight away so it can be
+// processed as it streams.
+fn reduce_stdin(letters: &Letters, format: Format) -> bool {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut reducer = Reducer::new();
+    let mut ok = true;
+
+    for (idx, line) in io::stdin().lock().lines().enumerate() {
+        let line = line.unwrap_or_else(|e| {
+            eprintln!("Error reading stdin: {}", e);
+            process::exit(1);
+        });
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match letters.to_word(line) {
+            Ok(word) if format == Format::Json => {
+                // We need the steps, so can't use the cache.
+                let steps = reduce(&word);
+                let report = BatchReport {
+                    input: letters.to_str(steps.start()),
+                    output: letters.to_str(steps.end()),
+                    steps: steps.num_operations(),
+                };
+                writeln!(out, "{}", serde_json::to_string(&report).unwrap()).unwrap();
+                out.flush().unwrap();
+            }
+            Ok(word) => {
+                writeln!(out, "{}", letters.display(&reducer.reduce_cached(&word))).unwrap()
+            }
+            Err(e) => {
+                eprintln!("Line {}: can't reduce '{}': {}", idx + 1, line, e);
+                ok = false;
+            }
+        }
+    }
+
+    ok
 }
 
-// Convert a string from LMR to LR. Doesn't eliminate overlap between
-// L and R.
-fn remove_middle(l: WordRef, m: WordRef, r: WordRef) -> Steps {
-    // Choose u s.t. L ~ LMRu
-    let (l_to_lmru, u) = &find_u(l, &chain(&[m, r]));
-    let lmru_to_l = l_to_lmru.time_rev();
-    // Choose v s.t. R ~ vLR
-    let (r_to_vlr, v) = &find_v(r, l);
-    let vlr_to_r = r_to_vlr.time_rev();
-
-    Steps::join(vec![
-        // LM(R) -> LM(vLR)
-        Steps::prefix(&[l, m], r_to_vlr),
-        //   LMv(LR) -> LMv(LRLR)
-        Steps::prefix(&[l, m, v], &Steps::square(&[l, r])),
-        // LM(vLR)LR -> LM(R)LR
-        Steps::prefix(&[l, m], &vlr_to_r.suffix(&[l, r])),
-        // LMR(L)R -> LMR(LMRu)R
-        Steps::prefix(&[l, m, r], &l_to_lmru.suffix(&[r])),
-        // (LMRLMR)uR -> (LMR)uR
-        Steps::square(&[l, m, r]).suffix(&[u, r]).time_rev(),
-        // (LMRu)R -> LR
-        lmru_to_l.suffix(&[r]),
-    ])
+// Settings and history for an interactive session.
+struct Repl {
+    letters: Letters,
+    verbose: bool,
+    color: bool,
+    generators: usize,
+    // Each word reduced, and what it reduced to.
+    history: Vec<(Word, Word)>,
 }
 
-////////////////////////////////////////////////////////////////////////
-// Main entry point.
-//
+const REPL_HELP: &str = "\
+Enter a word to reduce it, or one of:
+  :verbose on|off   Show the reduction steps
+  :generators <n>   Only accept words over the first n letters
+  :class <word>     List the shortest words equivalent to the word
+  :elements         List the elements of the monoid
+  :history          List the words reduced so far
+  :help             Show this message
+  :quit             Leave";
+
+impl Repl {
+    fn new(args: &Cli) -> Repl {
+        Repl {
+            letters: args.alphabet.clone(),
+            verbose: args.verbose,
+            color: args.color,
+            generators: args.generators,
+            history: Vec::new(),
+        }
+    }
 
-#[derive(Debug, Parser)]
-#[clap(name = "idem_monoid")]
-#[clap(about = "Tool for generating and reducing elements of an idempotent free monoid", long_about = None)]
-struct Cli {
-    /// Size of alphabet to use when generating the idempotent monoid.
-    #[clap(long, value_parser, default_value_t = 3)]
-    generators: usize,
+    // Parse a word, checking it only uses the current generators.
+    fn parse(&self, s: &str) -> Result<Word, String> {
+        let word = self.letters.to_word(s)?;
+        match word.iter().find(|sym| **sym as usize >= self.generators) {
+            Some(sym) => Err(format!(
+                "'{}' isn't one of the first {} letters",
+                self.letters.to_str(&[*sym]),
+                self.generators
+            )),
+            None => Ok(word),
+        }
+    }
 
-    /// Or a word to reduce to canonical form
-    #[clap(long, value_parser)]
-    reduce: Option<String>,
+    // Handle a ':' command, returning false if it's time to stop.
+    fn command(&mut self, cmd: &str, arg: &str) -> Result<bool, String> {
+        match (cmd, arg) {
+            ("verbose", "on") => self.verbose = true,
+            ("verbose", "off") => self.verbose = false,
+            ("verbose", _) => return Err(String::from("expected ':verbose on' or ':verbose off'")),
+            ("generators", n) => {
+                let n = n
+                    .parse::<usize>()
+                    .map_err(|_| format!("'{}' isn't a number", n))?;
+                if n > self.letters.alphabet.letters().len() {
+                    return Err(format!(
+                        "the alphabet only has {} letters",
+                        self.letters.alphabet.letters().len()
+                    ));
+                }
+                self.generators = n;
+            }
+            ("class", w) => {
+                let words = shortest_words(reduce(&self.parse(w)?).end());
+                for word in words.iter() {
+                    println!("{}", self.letters.display(word));
+                }
+            }
+            ("elements", _) => {
+                if monoid_size(self.generators).is_none_or(|size| size > 1_000_000) {
+                    return Err(format!(
+                        "too many elements on {} generators",
+                        self.generators
+                    ));
+                }
+                for word in monoid_iter(self.generators) {
+                    println!("{}", self.letters.display(&word));
+                }
+            }
+            ("history", _) => {
+                for (idx, (word, reduced)) in self.history.iter().enumerate() {
+                    println!(
+                        "{}: {} = {}",
+                        idx + 1,
+                        self.letters.display(word),
+                        self.letters.display(reduced)
+                    );
+                }
+            }
+            ("help", _) => println!("{}", REPL_HELP),
+            ("quit", _) => return Ok(false),
+            _ => return Err(format!("unknown command ':{}', try ':help'", cmd)),
+        }
+        Ok(true)
+    }
 
-    /// If reducing a word, show the reduction path?
-    #[clap(long, value_parser)]
-    verbose: bool,
+    // Handle a line of input, returning false if it's time to stop.
+    fn line(&mut self, line: &str) -> Result<bool, String> {
+        if let Some(cmd) = line.strip_prefix(':') {
+            let (cmd, arg) = cmd.split_once(' ').unwrap_or((cmd, ""));
+            return self.command(cmd, arg.trim());
+        }
+
+        let word = self.parse(line)?;
+        let steps = reduce(&word);
+        if self.verbose {
+            print!("{}", steps_text(&steps, &self.letters, self.color));
+        }
+        println!("{}", self.letters.display(steps.end()));
+        self.history.push((word, steps.end().clone()));
+        Ok(true)
+    }
+}
+
+// Read words and commands from stdin until it's closed or we're told
+// to stop.
+fn run_repl(args: &Cli) {
+    let mut repl = Repl::new(args);
+    let interactive = io::stdin().is_terminal();
+    if interactive {
+        println!("{}", REPL_HELP);
+    }
+
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        if interactive {
+            print!("> ");
+            io::stdout().flush().unwrap();
+        }
+        let line = match lines.next() {
+            Some(Ok(line)) => line,
+            Some(Err(e)) => {
+                eprintln!("Error reading stdin: {}", e);
+                process::exit(1);
+            }
+            None => break,
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match repl.line(line) {
+            Ok(true) => (),
+            Ok(false) => break,
+            Err(e) => eprintln!("Error: {}", e),
+        }
+    }
 }
 
 fn main() {
-    let args = Cli::parse();
+    let mut args = Cli::parse();
+    if let Err(e) = args.alphabet.set_empty(&args.empty_symbol) {
+        eprintln!("Invalid empty symbol: {}", e);
+        process::exit(1);
+    }
+
+    if args.profile_overlap && !OVERLAP_PROFILING {
+        eprintln!("--profile-overlap needs idem_monoid built with the \"profile\" feature");
+        process::exit(1);
+    }
 
-    if let Some(reduce_me) = args.reduce {
-        // Reduce the given word.
-        let as_word = str_to_word(&reduce_me);
-        let steps = reduce(&as_word);
+    if let Some(reduce_us) = &args.reduce {
+        // Reduce each of the comma-separated words.
+        let words = reduce_us.split(',').collect::<Vec<_>>();
+        let mut ok = true;
+        for (idx, reduce_me) in words.iter().enumerate() {
+            if words.len() > 1 && args.verbose && args.format == Format::Text {
+                if idx > 0 {
+                    println!();
+                }
+                println!("Reducing {}:", reduce_me);
+            }
+            ok &= reduce_and_print(reduce_me, &args);
+        }
+        if !ok {
+            process::exit(1);
+        }
+    } else if args.repl {
+        args.alphabet.check_size(args.generators);
+        run_repl(&args);
+    } else if args.reduce_stdin {
+        if !reduce_stdin(&args.alphabet, args.format) {
+            process::exit(1);
+        }
+    } else if let Some(check_us) = &args.is_canonical {
+        // Check each of the comma-separated words is already reduced.
+        let mut all_canonical = true;
+        for check_me in check_us.split(',') {
+            let canonical = is_canonical(&parse_word(check_me, &args.alphabet));
+            println!("{}", canonical);
+            all_canonical &= canonical;
+        }
+        if !all_canonical {
+            process::exit(1);
+        }
+    } else if let Some(multiply_me) = &args.multiply {
+        // Reduce the product of the two words.
+        let (l, r) = parse_pair(multiply_me, &args.alphabet);
+        let indices = (element_index(&l), element_index(&r));
+        match indices {
+            // If we only need the product of two elements over 3
+            // letters, we can look it up.
+            (Some(i), Some(j)) if only_prints_end_word(&args) => {
+                print_end_word(&element_from_index(multiply_indexed(i, j)), &args);
+            }
+            _ => {
+                let steps = reduce(&product(&l, &r, ConcatMode::Raw));
+                print_reduction(&steps, &args);
+            }
+        }
+    } else if let Some(power_me) = &args.power {
+        // Unsquare the power down to a single copy.
+        let (word, n) = match power_me.split_once(',') {
+            Some((word, n)) => match n.parse::<usize>() {
+                Ok(n) => (parse_word(word, &args.alphabet), n),
+                Err(_) => {
+                    eprintln!("Invalid power '{}', expected a number", n);
+                    process::exit(1);
+                }
+            },
+            None => {
+                eprintln!(
+                    "Expected a word and a power, e.g. aba,4, got '{}'",
+                    power_me
+                );
+                process::exit(1);
+            }
+        };
+        print_reduction(&power(&word, n), &args);
+    } else if let Some(n_words) = args.random {
+        args.alphabet.check_size(args.generators);
+        if args.generators == 0 && args.length > 0 {
+            eprintln!("Need at least one generator for non-empty random words");
+            process::exit(1);
+        }
+        for word in random_words(n_words, args.length, args.generators, args.seed) {
+            if args.reduced {
+                println!(
+                    "{} -> {}",
+                    args.alphabet.display(&word),
+                    args.alphabet.display(&reduce_word(&word))
+                );
+            } else {
+                println!("{}", args.alphabet.display(&word));
+            }
+        }
+    } else if args.table_indexed {
+        args.alphabet.check_size(args.generators);
+        for row in indexed_table(args.generators) {
+            println!(
+                "{}",
+                row.iter()
+                    .map(|idx| idx.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
+        }
+    } else if let Some(ExportFormat::Gap) = args.export {
+        args.alphabet.check_size(args.generators);
+        write_gap(&args);
+    } else if let Some(compare_me) = &args.equivalent {
+        // Check whether the two words reduce to the same thing.
+        let (l, r) = parse_pair(compare_me, &args.alphabet);
+        let l_steps = reduce(&l);
+        let r_steps = reduce(&r);
         if args.verbose {
-            println!("{}", steps);
+            print_side_by_side(&l_steps, &r_steps, &args.alphabet);
+        }
+        let equivalent = l_steps.end() == r_steps.end();
+        println!("{}", equivalent);
+        if !equivalent {
+            process::exit(1);
+        }
+    } else if let Some(compare_me) = &args.first_difference {
+        // Reduce both words, and find the first position they differ.
+        let (l, r) = parse_pair(compare_me, &args.alphabet);
+        let l_red = reduce_word(&l);
+        let r_red = reduce_word(&r);
+        println!("{}", args.alphabet.display(&l_red));
+        println!("{}", args.alphabet.display(&r_red));
+        let diff = (0..l_red.len().max(r_red.len())).find(|idx| l_red.get(*idx) != r_red.get(*idx));
+        match diff {
+            None => println!("Normal forms are the same"),
+            Some(idx) => {
+                // One word may have run out.
+                let sym_at = |word: &Word| match word.get(idx) {
+                    Some(sym) => format!("'{}'", args.alphabet.to_str(&[*sym])),
+                    None => String::from("the end"),
+                };
+                println!(
+                    "Normal forms first differ at position {}: {} vs {}",
+                    idx + 1,
+                    sym_at(&l_red),
+                    sym_at(&r_red)
+                );
+                process::exit(1);
+            }
+        }
+    } else if let Some(compare_me) = &args.leq {
+        // Check whether the first word is below the second.
+        let (l, r) = parse_pair(compare_me, &args.alphabet);
+        let below = leq(&l, &r);
+        println!("{}", below);
+        if !below {
+            process::exit(1);
+        }
+    } else if args.size {
+        // Count the elements without generating them.
+        match monoid_size(args.generators) {
+            Some(size) => println!("{}", size),
+            None => {
+                eprintln!(
+                    "Monoid on {} generators is too big to count",
+                    args.generators
+                );
+                process::exit(1);
+            }
+        }
+    } else if let Some(expected) = args.assert_size {
+        // Count the distinct elements, as --distinct would keep.
+        let found = monoid_iter(args.generators).collect::<HashSet<_>>().len();
+        if found != expected {
+            eprintln!(
+                "Generated {} distinct elements, but expected {}",
+                found, expected
+            );
+            process::exit(1);
+        }
+        println!("Generated {} distinct elements, as expected", found);
+    } else if args.estimate {
+        // Estimate how much generation would produce, without doing
+        // it.
+        let (log_elements, log_bytes) = estimate_output(args.generators);
+        if !log_bytes.is_finite() {
+            eprintln!(
+                "Monoid on {} generators is too big to estimate",
+                args.generators
+            );
+            process::exit(1);
+        }
+        match monoid_size(args.generators) {
+            Some(size) => println!("{} elements", size),
+            None => println!("About {} elements", format_log10(log_elements)),
+        }
+        if log_bytes < 15.0 {
+            println!("At most {:.0} bytes of output", 10f64.powf(log_bytes));
+        } else {
+            println!("At most {} bytes of output", format_log10(log_bytes));
+        }
+    } else if args.histogram_of_lengths {
+        // Tally the elements by length.
+        let counts = length_histogram(args.generators);
+        println!("Length  Elements");
+        for (len, count) in counts.iter().enumerate() {
+            println!("{:>6}  {:>8}", len, count);
+        }
+    } else if args.longest {
+        // Find the length first, then the words that reach it.
+        args.alphabet.check_size(args.generators);
+        let max_len = max_canonical_len(args.generators);
+        println!("Longest canonical words have length {}:", max_len);
+        for word in monoid_iter(args.generators).filter(|word| word.len() == max_len) {
+            println!("{}", args.alphabet.display(&word));
+        }
+    } else if args.cayley {
+        // Print the edges x -> xg for each element x and generator g.
+        args.alphabet.check_size(args.generators);
+        check_small_monoid(args.generators, "");
+        let edges = cayley_graph(args.generators);
+        let letters = &args.alphabet;
+        if args.format == Format::Dot {
+            println!("digraph cayley {{");
+            for (x, g, xg) in edges.iter() {
+                println!(
+                    "    \"{}\" -> \"{}\" [label=\"{}\"];",
+                    letters.display(x),
+                    letters.display(xg),
+                    letters.to_str(&[*g])
+                );
+            }
+            println!("}}");
+        } else {
+            for (x, g, xg) in edges.iter() {
+                println!(
+                    "{} -{}-> {}",
+                    letters.display(x),
+                    letters.to_str(&[*g]),
+                    letters.display(xg)
+                );
+            }
+        }
+    } else if args.commuting {
+        args.alphabet.check_size(args.generators);
+        check_small_monoid(args.generators, "");
+        let pairs = commuting_pairs(args.generators);
+        for (x, y) in pairs.iter() {
+            println!("{} {}", args.alphabet.display(x), args.alphabet.display(y));
+        }
+        // Generators are the elements of length 1.
+        let generator_pairs = pairs
+            .iter()
+            .filter(|(x, y)| x.len() == 1 && y.len() == 1)
+            .count();
+        println!(
+            "{} commuting pairs, {} of them between generators",
+            pairs.len(),
+            generator_pairs
+        );
+    } else if args.structure {
+        // Find the elements that act specially under multiplication.
+        args.alphabet.check_size(args.generators);
+        check_small_monoid(args.generators, "");
+        let special = special_elements(args.generators);
+        let show = |words: &[Word]| {
+            if words.is_empty() {
+                "none".to_string()
+            } else {
+                words
+                    .iter()
+                    .map(|w| args.alphabet.display(w))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            }
+        };
+        println!("Identity: {}", show(&special.identities));
+        println!("Left zeros: {}", show(&special.left_zeros));
+        println!("Right zeros: {}", show(&special.right_zeros));
+    } else if args.green {
+        args.alphabet.check_size(args.generators);
+        check_small_monoid(args.generators, "");
+        for (idx, d) in green_classes(args.generators).iter().enumerate() {
+            if idx > 0 {
+                println!();
+            }
+            println!(
+                "D-class {}: {} R-classes, {} L-classes",
+                idx + 1,
+                d.r_classes.len(),
+                d.l_classes.len()
+            );
+            let cells = (0..d.r_classes.len())
+                .map(|r| {
+                    (0..d.l_classes.len())
+                        .map(|l| args.alphabet.display(d.cell(r, l)))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            let width = cells.iter().flatten().map(|s| s.len()).max().unwrap_or(0);
+            for row in cells.iter() {
+                let row = row
+                    .iter()
+                    .map(|s| format!("{:width$}", s, width = width))
+                    .collect::<Vec<_>>();
+                println!("  {}", row.join(" ").trim_end());
+            }
+        }
+    } else if args.check_closure {
+        args.alphabet.check_size(args.generators);
+        check_small_monoid(args.generators, "");
+        // Check every product is an element.
+        let (checked, failures) = check_closure(args.generators);
+        for (l, r, product) in failures.iter() {
+            println!(
+                "{} * {} = {} is not an element",
+                args.alphabet.display(l),
+                args.alphabet.display(r),
+                args.alphabet.display(product)
+            );
+        }
+        println!(
+            "Checked {} products, {} not in the monoid",
+            checked,
+            failures.len()
+        );
+        if !failures.is_empty() {
+            process::exit(1);
+        }
+    } else if args.check_associativity {
+        args.alphabet.check_size(args.generators);
+        // Random triples are fine however big the monoid is.
+        if args.sample.is_none() {
+            check_small_monoid(args.generators, ", or --sample to check random triples");
+        }
+        // Check (ab)c = a(bc).
+        let (checked, failures) = check_associativity(args.generators, args.sample);
+        for failure in failures.iter() {
+            println!(
+                "({} * {}) * {} = {}, but {} * ({} * {}) = {}",
+                args.alphabet.display(&failure.a),
+                args.alphabet.display(&failure.b),
+                args.alphabet.display(&failure.c),
+                args.alphabet.display(&failure.ab_c),
+                args.alphabet.display(&failure.a),
+                args.alphabet.display(&failure.b),
+                args.alphabet.display(&failure.c),
+                args.alphabet.display(&failure.a_bc)
+            );
+        }
+        println!(
+            "Checked {} triples, {} not associative",
+            checked,
+            failures.len()
+        );
+        if !failures.is_empty() {
+            process::exit(1);
+        }
+    } else if args.check_idempotent {
+        args.alphabet.check_size(args.generators);
+        // Check ww = w.
+        let (checked, failures) = check_idempotent(args.generators);
+        for (w, square) in failures.iter() {
+            println!(
+                "{} * {} = {}, not {}",
+                args.alphabet.display(w),
+                args.alphabet.display(w),
+                args.alphabet.display(square),
+                args.alphabet.display(w)
+            );
+        }
+        println!(
+            "Checked {} elements, {} not idempotent",
+            checked,
+            failures.len()
+        );
+        if !failures.is_empty() {
+            process::exit(1);
         }
-        let as_str = word_to_str(&steps.end);
-        println!("{}", as_str);
     } else {
         // Generate all the elements of the monad.
-        let words = generate_monoid(args.generators);
-
-        for word in words {
-            let word_str = if word.is_empty() {
-                "0".to_string()
-            } else {
-                word_to_str(&word)
-            };
-            println!("{}", word_str);
+        args.alphabet.check_size(args.generators);
+        let result = match &args.output {
+            Some(path) => File::create(path).and_then(|mut file| write_generated(&args, &mut file)),
+            None => write_generated(&args, &mut io::stdout().lock()),
+        };
+        if let Err(e) = result {
+            eprintln!("Error writing elements: {}", e);
+            process::exit(1);
         }
     }
 }
@@ -0,0 +1,57 @@
+////////////////////////////////////////////////////////////////////////
+// Counters for profiling the search for overlaps between words. They
+// are only compiled in with the "profile" feature, and cost nothing
+// otherwise.
+//
+
+#[cfg(feature = "profile")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::Sym;
+
+/// Counts of the work done finding overlaps between words, as
+/// `overlap_len` does.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct OverlapProfile {
+    /// Number of overlaps searched for.
+    pub searches: u64,
+    /// Number of pairs of symbols compared while searching.
+    pub comparisons: u64,
+}
+
+/// Is overlap profiling compiled in?
+pub const OVERLAP_PROFILING: bool = cfg!(feature = "profile");
+
+#[cfg(feature = "profile")]
+static SEARCHES: AtomicU64 = AtomicU64::new(0);
+#[cfg(feature = "profile")]
+static COMPARISONS: AtomicU64 = AtomicU64::new(0);
+
+// Count an overlap search.
+#[inline]
+pub(crate) fn count_search() {
+    #[cfg(feature = "profile")]
+    SEARCHES.fetch_add(1, Ordering::Relaxed);
+}
+
+// Compare two symbols, counting the comparison.
+#[inline]
+pub(crate) fn sym_eq(a: Sym, b: Sym) -> bool {
+    #[cfg(feature = "profile")]
+    COMPARISONS.fetch_add(1, Ordering::Relaxed);
+    a == b
+}
+
+/// Return the counts so far, and reset them. Without the "profile"
+/// feature, nothing is counted, and the counts are always zero.
+pub fn take_overlap_profile() -> OverlapProfile {
+    #[cfg(feature = "profile")]
+    {
+        OverlapProfile {
+            searches: SEARCHES.swap(0, Ordering::Relaxed),
+            comparisons: COMPARISONS.swap(0, Ordering::Relaxed),
+        }
+    }
+    #[cfg(not(feature = "profile"))]
+    OverlapProfile::default()
+}
@@ -0,0 +1,787 @@
+////////////////////////////////////////////////////////////////////////
+// Word reduction
+//
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+
+use crate::{
+    chain, content, merge, overlap_len, Alphabet, ContentMask, Steps, Sym, Word, WordRef,
+    NUM_LETTERS,
+};
+
+/// Reasons a word can't be reduced.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ReduceError {
+    /// A character in the input that isn't a lower-case letter.
+    InvalidChar(char),
+    /// A symbol that can't be written as a letter.
+    InvalidSym(Sym),
+    /// A symbol that should appear in a word, but doesn't.
+    MissingSym(Sym),
+    /// A word split on a number of letters it doesn't have: the number
+    /// asked for, and the number the word uses.
+    WrongLetterCount(usize, usize),
+}
+
+impl fmt::Display for ReduceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReduceError::InvalidChar(c) => {
+                write!(f, "invalid character '{}', expected 'a' to 'z'", c)
+            }
+            ReduceError::InvalidSym(s) => {
+                write!(f, "invalid symbol {}, expected 0 to {}", s, NUM_LETTERS - 1)
+            }
+            ReduceError::MissingSym(s) => {
+                write!(f, "symbol {} doesn't appear in the word", s)
+            }
+            ReduceError::WrongLetterCount(requested, found) => {
+                write!(
+                    f,
+                    "can't split on {} letters, the word uses {}",
+                    requested, found
+                )
+            }
+        }
+    }
+}
+
+impl Error for ReduceError {}
+
+// Find the length of the longest left subword using n distinct
+// letters. The word must use more than n letters.
+fn find_left_subword(word: WordRef, n: usize) -> usize {
+    let mut letters = ContentMask::new();
+    for (idx, sym) in word.iter().enumerate() {
+        letters.insert(*sym);
+        if letters.len() == n + 1 {
+            return idx;
+        }
+    }
+    panic!("Oh dear, not enough distinct letters (shouldn't happen!)");
+}
+
+// Find the index of the start of the longest right subword using n
+// distinct letters. The word must use more than n letters.
+fn find_right_subword(word: WordRef, n: usize) -> usize {
+    let mut letters = ContentMask::new();
+    for (idx, sym) in word.iter().enumerate().rev() {
+        letters.insert(*sym);
+        if letters.len() == n + 1 {
+            return idx + 1;
+        }
+    }
+    panic!("Oh dear, not enough distinct letters (shouldn't happen!)");
+}
+
+// Reduce the left sub-word that uses all but one of the characters in
+// the word. `n_letters` must be between 1 and the number of letters
+// the word uses, as checked by `check_letter_count`.
+fn reduce_left(word: WordRef, n_letters: usize) -> Steps {
+    let len = find_left_subword(word, n_letters - 1);
+    let to_reduce = &word[..len];
+    let rest = &word[len..];
+    reduce(to_reduce).into_suffixed(&[rest])
+}
+
+// Same, but for the right.
+fn reduce_right(word: WordRef, n_letters: usize) -> Steps {
+    let len = find_right_subword(word, n_letters - 1);
+    let to_reduce = &word[len..];
+    let rest = word[..len].to_vec();
+    reduce(to_reduce).into_prefixed(&[&rest])
+}
+
+// Like `merge`, but returns steps. Finds the unsquaring the maximally
+// shortens the word.
+fn reduce_middle(left: WordRef, right: WordRef) -> Steps {
+    let overlap = overlap_len(left, right);
+    if overlap == 0 {
+        return Steps::empty(&chain(&[left, right]));
+    }
+
+    // Build the unsquaring operation to eliminate the overlap.
+    let idx = left.len() - overlap;
+    let l = &left[..idx];
+    let m = &left[idx..];
+    let r = &right[overlap..];
+    Steps::square(&[m])
+        .into_suffixed(&[r])
+        .into_prefixed(&[l])
+        .into_time_rev()
+}
+
+// Once the left and right subwords using all but one letter have
+// been reduced, remove what's between the left and right subwords
+// using all the letters, and then the overlap between them.
+fn reduce_centre(word: WordRef, n_letters: usize) -> Vec<Steps> {
+    let mut steps = Vec::new();
+
+    // Extract the left and right shortest words using all the letters
+    // (one longer than the longest words using all but one letter!).
+    let l_len = find_left_subword(word, n_letters - 1) + 1;
+    let l_word = word[..l_len].to_vec();
+
+    let r_idx = find_right_subword(word, n_letters - 1) - 1;
+    let r_word = word[r_idx..].to_vec();
+
+    // If the left and right subwords overlap no further reduction is
+    // possible, they're already in minimal form.
+    if l_len <= r_idx {
+        // Only try to remove a middle section if there is one.
+        if l_len < r_idx {
+            steps.push(remove_middle(&l_word, &word[l_len..r_idx], &r_word));
+        }
+
+        // Then remove overlap between left and right subwords.
+        steps.push(reduce_middle(&l_word, &r_word));
+    }
+
+    steps
+}
+
+// Is the word a power of a single letter?
+fn is_letter_power(word: WordRef) -> bool {
+    word.iter().all(|sym| *sym == word[0])
+}
+
+// Reduce a non-empty power of a single letter to that letter.
+fn reduce_letter_power(word: WordRef) -> Steps {
+    power(&word[..1], word.len())
+}
+
+/// The steps from the word repeated n times down to the word itself,
+/// by unsquaring the first two copies until only one is left. For
+/// n = 0 the power is the empty word, so there's nothing to do.
+pub fn power(word: WordRef, n: usize) -> Steps {
+    if n <= 1 || word.is_empty() {
+        return Steps::empty(&word.repeat(n));
+    }
+
+    let mut steps = vec![Steps::empty(&word.repeat(n))];
+    for count in (2..=n).rev() {
+        let rest = word.repeat(count - 2);
+        steps.push(
+            Steps::square(&[word])
+                .into_suffixed(&[&rest])
+                .into_time_rev(),
+        );
+    }
+    Steps::join(steps)
+}
+
+/// Given a word, produces the steps that maximally shortens it to
+/// normal form. The normal form is the shortest word equivalent to
+/// the input, and is the form produced by `generate_monoid`.
+pub fn reduce(word: WordRef) -> Steps {
+    // Base case - do nothing for empty string.
+    if word.is_empty() {
+        return Steps::empty(word);
+    }
+
+    // Another base case, which the general case would make a meal of.
+    if is_letter_power(word) {
+        return reduce_letter_power(word);
+    }
+
+    // Get alphabet size.
+    let n_letters = ContentMask::of(word).len();
+
+    // Place to accumulate the steps performed:
+    let mut steps = Vec::new();
+
+    // Reduce the subwords (using n - 1 letters) on the left and right.
+    steps.push(reduce_left(word, n_letters));
+    let word = steps.last().unwrap().end();
+    steps.push(reduce_right(word, n_letters));
+    let word = steps.last().unwrap().end();
+
+    steps.extend(reduce_centre(word, n_letters));
+
+    // Squaring and unsquaring never change the letters used, so if a
+    // stage does, there's a bug.
+    for stage in steps.iter() {
+        debug_assert_eq!(
+            content(stage.start()),
+            content(stage.end()),
+            "Reduction stage changed content"
+        );
+    }
+    Steps::join(steps)
+}
+
+/// The normal form of a word, the same as `reduce(word).end()`, but
+/// without recording the steps taken. Follows the same recursion as
+/// `reduce`, working on the words directly, so it's much cheaper when
+/// only the normal form is wanted. Has the same limit on symbols as
+/// `reduce`.
+pub fn reduce_word(word: WordRef) -> Word {
+    if word.is_empty() {
+        return Word::new();
+    }
+    if is_letter_power(word) {
+        return vec![word[0]];
+    }
+
+    let n_letters = ContentMask::of(word).len();
+
+    // Reduce the subwords on the left and right, as `reduce_left`
+    // and `reduce_right` do.
+    let len = find_left_subword(word, n_letters - 1);
+    let word = chain(&[&reduce_word(&word[..len]), &word[len..]]);
+    let idx = find_right_subword(&word, n_letters - 1);
+    let word = chain(&[&word[..idx], &reduce_word(&word[idx..])]);
+
+    // Then drop the middle, and the overlap, as `reduce_centre` does.
+    let l_len = find_left_subword(&word, n_letters - 1) + 1;
+    let r_idx = find_right_subword(&word, n_letters - 1) - 1;
+    if l_len <= r_idx {
+        merge(&word[..l_len], &word[r_idx..])
+    } else {
+        word
+    }
+}
+
+/// The recursive structure of a reduction, as performed by `reduce`:
+/// the longest left and right subwords missing a letter are reduced
+/// first, and then what's left in the middle.
+#[derive(Clone)]
+pub struct ReduceTree {
+    /// The reduction of the longest left subword using all but one
+    /// letter, or None if that subword is empty.
+    pub left: Option<Box<ReduceTree>>,
+    /// The same, for the right subword.
+    pub right: Option<Box<ReduceTree>>,
+    /// The steps taken after the left and right subwords are reduced.
+    pub middle: Steps,
+    /// All the steps at this level, with the left and right subwords'
+    /// steps in the context of the whole word.
+    pub steps: Steps,
+}
+
+impl ReduceTree {
+    /// Write the tree out as `Display` does, but writing the words
+    /// with the given alphabet.
+    pub fn to_string_with(&self, alphabet: &Alphabet) -> String {
+        let mut res = String::new();
+        self.write_indented(&mut res, alphabet, "", 0).unwrap();
+        res
+    }
+
+    // Write the tree out, indenting each level further.
+    fn write_indented<W: fmt::Write>(
+        &self,
+        out: &mut W,
+        alphabet: &Alphabet,
+        label: &str,
+        depth: usize,
+    ) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        writeln!(
+            out,
+            "{}{}{} -> {}",
+            indent,
+            label,
+            alphabet.encode(self.steps.start()),
+            alphabet.encode(self.steps.end())
+        )?;
+        if let Some(left) = &self.left {
+            left.write_indented(out, alphabet, "left: ", depth + 1)?;
+        }
+        if let Some(right) = &self.right {
+            right.write_indented(out, alphabet, "right: ", depth + 1)?;
+        }
+        if self.middle.num_operations() > 0 {
+            writeln!(out, "{}  middle:", indent)?;
+            for (l, r) in self.middle.steps_with(alphabet).iter() {
+                writeln!(out, "{}    {} -> {}", indent, l, r)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ReduceTree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_indented(f, &Alphabet::lowercase(), "", 0)
+    }
+}
+
+/// Like `reduce`, but returns the tree of recursive reductions, rather
+/// than just the steps. The steps at the root are the same as those
+/// `reduce` produces.
+pub fn reduce_tree(word: WordRef) -> ReduceTree {
+    if word.is_empty() {
+        return ReduceTree {
+            left: None,
+            right: None,
+            middle: Steps::empty(word),
+            steps: Steps::empty(word),
+        };
+    }
+
+    if is_letter_power(word) {
+        let steps = reduce_letter_power(word);
+        return ReduceTree {
+            left: None,
+            right: None,
+            middle: steps.clone(),
+            steps,
+        };
+    }
+
+    let n_letters = ContentMask::of(word).len();
+    // Reduce a subword, unless it's empty.
+    let subtree = |subword: WordRef| {
+        if subword.is_empty() {
+            None
+        } else {
+            Some(Box::new(reduce_tree(subword)))
+        }
+    };
+
+    // Reduce the left and right subwords, as `reduce_left` and
+    // `reduce_right` do.
+    let l_len = find_left_subword(word, n_letters - 1);
+    let left = subtree(&word[..l_len]);
+    let left_steps = match &left {
+        Some(tree) => tree.steps.suffix(&[&word[l_len..]]),
+        None => Steps::empty(word),
+    };
+    let word = left_steps.end();
+
+    let r_idx = find_right_subword(word, n_letters - 1);
+    let right = subtree(&word[r_idx..]);
+    let right_steps = match &right {
+        Some(tree) => Steps::prefix(&[&word[..r_idx]], &tree.steps),
+        None => Steps::empty(word),
+    };
+    let word = right_steps.end();
+
+    let mut middle = vec![Steps::empty(word)];
+    middle.extend(reduce_centre(word, n_letters));
+    let middle = Steps::join(middle);
+    let steps = Steps::join(vec![left_steps, right_steps, middle.clone()]);
+    ReduceTree {
+        left,
+        right,
+        middle,
+        steps,
+    }
+}
+
+// Check the word can be split as `reduce_left` and `reduce_right`
+// need: it must use at least `n_letters` distinct letters, so there's
+// a longest subword using `n_letters - 1` of them, and `n_letters`
+// can't be 0.
+fn check_letter_count(word: WordRef, n_letters: usize) -> Result<(), ReduceError> {
+    let found = content(word).len();
+    if n_letters == 0 || n_letters > found {
+        return Err(ReduceError::WrongLetterCount(n_letters, found));
+    }
+    Ok(())
+}
+
+/// Reduce the longest prefix of the word using `n_letters - 1`
+/// distinct letters, leaving the rest of the word alone. This is the
+/// first thing `reduce` does, with `n_letters` the number of letters
+/// in the word. Returns an error if the word uses fewer than
+/// `n_letters` letters, or `n_letters` is 0, as there's no such
+/// prefix to split off.
+pub fn try_reduce_left(word: WordRef, n_letters: usize) -> Result<Steps, ReduceError> {
+    check_letter_count(word, n_letters)?;
+    Ok(reduce_left(word, n_letters))
+}
+
+/// Like `try_reduce_left`, but for the longest suffix.
+pub fn try_reduce_right(word: WordRef, n_letters: usize) -> Result<Steps, ReduceError> {
+    check_letter_count(word, n_letters)?;
+    Ok(reduce_right(word, n_letters))
+}
+
+/// Like `reduce`, but checks the word is over the 'a' to 'z'
+/// alphabet first, returning an error rather than misbehaving.
+pub fn try_reduce(word: WordRef) -> Result<Steps, ReduceError> {
+    if let Some(sym) = word.iter().find(|sym| **sym as usize >= NUM_LETTERS) {
+        return Err(ReduceError::InvalidSym(*sym));
+    }
+    Ok(reduce(word))
+}
+
+/// Like `reduce`, but also follows the letter at position `pos`
+/// through the reduction, returning where it ends up in the normal
+/// form, or None if it's absorbed when a square is removed. See
+/// `Steps::track_position` for how letters move.
+pub fn reduce_tracked(word: WordRef, pos: usize) -> (Steps, Option<usize>) {
+    let steps = reduce(word);
+    let tracked = steps.track_position(pos);
+    (steps, tracked)
+}
+
+/// Is the word already in the normal form produced by `reduce`?
+pub fn is_canonical(word: WordRef) -> bool {
+    reduce(word).end() == word
+}
+
+/// Find the steps to go from one word to another, by going through
+/// their common normal form. Returns None if they're not equivalent.
+pub fn steps_between(a: WordRef, b: WordRef) -> Option<Steps> {
+    let a_steps = reduce(a);
+    let b_steps = reduce(b);
+    if a_steps.end() != b_steps.end() {
+        return None;
+    }
+    Some(Steps::join(vec![a_steps, b_steps.time_rev()]))
+}
+
+// All the words reachable from the given one by squaring or
+// unsquaring a single factor, without going over `max_len`, along
+// with the factor of the shorter word operated on, as (start,
+// length).
+fn rewrites(current: WordRef, max_len: usize) -> Vec<(Word, usize, usize)> {
+    let n = current.len();
+    let mut nexts = Vec::new();
+    // Unsquare each square factor.
+    for len in 1..=n / 2 {
+        for idx in 0..=n - 2 * len {
+            if current[idx..idx + len] == current[idx + len..idx + 2 * len] {
+                nexts.push((
+                    chain(&[&current[..idx + len], &current[idx + 2 * len..]]),
+                    idx,
+                    len,
+                ));
+            }
+        }
+    }
+    // Square each factor, if the result isn't too long.
+    for len in 1..=n.min(max_len.saturating_sub(n)) {
+        for idx in 0..=n - len {
+            nexts.push((chain(&[&current[..idx + len], &current[idx..]]), idx, len));
+        }
+    }
+    nexts
+}
+
+/// Like `reduce`, but finds a derivation with the fewest squaring and
+/// unsquaring steps, by breadth-first search through the words
+/// reachable from the given one. Only words at most `max_len` long
+/// are considered, to keep the search tractable, so this returns None
+/// if every derivation passes through a longer word. Even so, the
+/// search is exponential in `max_len`, so is only for small words.
+pub fn reduce_minimal(word: WordRef, max_len: usize) -> Option<Steps> {
+    let target = reduce(word).end().clone();
+
+    // For each word found, the word it was reached from, and the
+    // factor of the shorter of the two that was squared or
+    // unsquared, as (start, length).
+    let mut parents: HashMap<Word, Option<(Word, usize, usize)>> = HashMap::new();
+    parents.insert(word.to_vec(), None);
+    let mut queue = VecDeque::from([word.to_vec()]);
+
+    while let Some(current) = queue.pop_front() {
+        if current == target {
+            return Some(minimal_path(&parents, current));
+        }
+
+        let nexts = rewrites(&current, max_len);
+        for (next, idx, len) in nexts {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some((current.clone(), idx, len)));
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Lazily enumerate the words equivalent to the given one that can
+/// be reached from it by squaring and unsquaring without going
+/// through a word longer than `max_len`, in breadth-first order,
+/// starting with the word itself. Each word is produced once. Every
+/// word in the class is reached for a large enough `max_len`, but as
+/// classes are infinite, this only stops because of the bound.
+pub fn class_of(word: WordRef, max_len: usize) -> impl Iterator<Item = Word> {
+    let mut seen = HashSet::from([word.to_vec()]);
+    let mut queue = VecDeque::from([word.to_vec()]);
+    std::iter::from_fn(move || {
+        let current = queue.pop_front()?;
+        for (next, _, _) in rewrites(&current, max_len) {
+            if seen.insert(next.clone()) {
+                queue.push_back(next);
+            }
+        }
+        Some(current)
+    })
+}
+
+// Build the steps for the path found by `reduce_minimal`, from the
+// start word to the given word.
+fn minimal_path(parents: &HashMap<Word, Option<(Word, usize, usize)>>, end: Word) -> Steps {
+    let mut steps = Vec::new();
+    let mut current = end;
+    while let Some((prev, idx, len)) = &parents[&current] {
+        let (idx, len) = (*idx, *len);
+        let squaring = current.len() > prev.len();
+        let short = if squaring { prev } else { &current };
+        let step = Steps::square(&[&short[idx..idx + len]])
+            .into_prefixed(&[&short[..idx]])
+            .into_suffixed(&[&short[idx + len..]]);
+        steps.push(if squaring { step } else { step.into_time_rev() });
+        current = prev.clone();
+    }
+
+    if steps.is_empty() {
+        return Steps::empty(&current);
+    }
+    steps.reverse();
+    Steps::join(steps)
+}
+
+/// Reduces words to normal form, remembering the results. As a
+/// word's normal form only depends on its class, a word can be
+/// reduced by reducing its longest already-seen prefix, and then the
+/// rest. Useful when reducing many words that share prefixes. The
+/// cache grows without limit.
+#[derive(Default)]
+pub struct Reducer {
+    cache: HashMap<Word, Word>,
+}
+
+impl Reducer {
+    /// Create a reducer with an empty cache.
+    pub fn new() -> Reducer {
+        Reducer::default()
+    }
+
+    /// The normal form of the word, the same as `reduce(word).end()`.
+    pub fn reduce_cached(&mut self, word: WordRef) -> Word {
+        if let Some(reduced) = self.cache.get(word) {
+            return reduced.clone();
+        }
+
+        // Find the longest prefix we already know the reduction of.
+        let known = (1..word.len())
+            .rev()
+            .find_map(|len| self.cache.get(&word[..len]).map(|r| (len, r)));
+        let reduced = match known {
+            Some((len, prefix)) => reduce_word(&chain(&[prefix, &word[len..]])),
+            None => reduce_word(word),
+        };
+
+        self.cache.insert(word.to_vec(), reduced.clone());
+        reduced
+    }
+
+    /// Number of words whose reductions are cached.
+    pub fn cache_len(&self) -> usize {
+        self.cache.len()
+    }
+}
+
+/// Iterator adaptor that reduces each word to normal form, as
+/// returned by `reduce_all`.
+pub struct ReduceAll<I> {
+    words: I,
+    reducer: Reducer,
+}
+
+impl<I: Iterator<Item = Word>> Iterator for ReduceAll<I> {
+    type Item = Word;
+
+    fn next(&mut self) -> Option<Word> {
+        self.words
+            .next()
+            .map(|word| self.reducer.reduce_cached(&word))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.words.size_hint()
+    }
+}
+
+/// Lazily reduce each of the words to normal form. Uses a `Reducer`,
+/// so repeated words and shared prefixes are cheap.
+pub fn reduce_all<I: IntoIterator<Item = Word>>(words: I) -> ReduceAll<I::IntoIter> {
+    ReduceAll {
+        words: words.into_iter(),
+        reducer: Reducer::new(),
+    }
+}
+
+/// Adds `reduce_all` as a method on iterators of words, so it can be
+/// chained with other adaptors.
+pub trait ReduceAllExt: Iterator<Item = Word> + Sized {
+    /// See `reduce_all`.
+    fn reduce_all(self) -> ReduceAll<Self> {
+        reduce_all(self)
+    }
+}
+
+impl<I: Iterator<Item = Word>> ReduceAllExt for I {}
+
+// Does the word end in a square?
+fn ends_in_square(word: WordRef) -> bool {
+    let len = word.len();
+    (1..=len / 2).any(|half| word[len - 2 * half..len - half] == word[len - half..])
+}
+
+/// Does the word have no factor of the form ww? Normal forms are
+/// always square-free, as unsquaring would make them shorter, but
+/// most square-free words aren't normal forms.
+pub fn is_square_free(word: WordRef) -> bool {
+    // Any square ends somewhere, so check each prefix.
+    (1..=word.len()).all(|end| !ends_in_square(&word[..end]))
+}
+
+// Depth-first search through the square-free words of the given
+// length over the given letters, in lexicographic order, collecting
+// those equivalent to the target until we have `limit` of them.
+fn find_equivalent(
+    word: &mut Word,
+    len: usize,
+    letters: &[Sym],
+    target: WordRef,
+    found: &mut Vec<Word>,
+    limit: usize,
+) {
+    if word.len() == len {
+        if content(word).len() == letters.len() && reduce(word).end() == target {
+            found.push(word.clone());
+        }
+        return;
+    }
+    for sym in letters.iter() {
+        word.push(*sym);
+        if !ends_in_square(word) {
+            find_equivalent(word, len, letters, target, found, limit);
+        }
+        word.pop();
+        if found.len() >= limit {
+            return;
+        }
+    }
+}
+
+// Find up to `limit` of the shortest words equivalent to the given
+// word, in lexicographic order.
+fn shortest_equivalents(word: WordRef, limit: usize) -> Vec<Word> {
+    let target = reduce(word).end().clone();
+    let letters = content(word);
+    let mut found = Vec::new();
+    find_equivalent(
+        &mut Vec::new(),
+        target.len(),
+        &letters,
+        &target,
+        &mut found,
+        limit,
+    );
+    found
+}
+
+/// Find the shortlex-least word equivalent to the given word.
+///
+/// `reduce` finds a shortest equivalent word, so this only differs
+/// from `reduce(word).end()` if a class has several shortest words.
+/// For up to 3 letters, the shortest word is unique, and the two
+/// agree. This works by searching all the square-free words of that
+/// length (any word with a square can be shortened), so is much
+/// slower than `reduce`, and only practical for short words: the
+/// longer 4-letter elements are already out of reach.
+pub fn canonical(word: WordRef) -> Word {
+    // The reduced word itself is always found, so this can't fail.
+    shortest_equivalents(word, 1).remove(0)
+}
+
+/// All the shortest words equivalent to the given word, in
+/// lexicographic order. Searches like `canonical`, so has the same
+/// limits on the length of word it's practical for.
+pub fn shortest_words(word: WordRef) -> Vec<Word> {
+    shortest_equivalents(word, usize::MAX)
+}
+
+////////////////////////////////////////////////////////////////////////
+// Core reduction algorithm, from Lothaire.
+//
+
+/// Given x and y, where y only uses letters in x, find u such that
+/// x ~ xyu. Returns the steps to go from x to xyu, and u.
+///
+/// This is the core construction from Lothaire: each letter of y is
+/// added by squaring the factor from the last occurrence of that
+/// letter to the end of the word built so far. Fails if y contains a
+/// letter not in x.
+pub fn find_u(x: WordRef, y: WordRef) -> Result<(Steps, Word), ReduceError> {
+    // Keep squaring appropriate subwords to build up a word of the
+    // form xyu. 'l' holds the word left of the insertion point, 'r'
+    // the word to the right.
+    let mut l = x.to_vec();
+    let mut r: Word = Vec::new();
+
+    let mut steps = vec![Steps::empty(x)];
+
+    for sym in y.iter() {
+        let (repeat_point, _) = l
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, sym2)| **sym2 == *sym)
+            .ok_or(ReduceError::MissingSym(*sym))?;
+
+        steps.push(
+            Steps::square(&[&l[repeat_point..]])
+                .into_prefixed(&[&l[..repeat_point]])
+                .into_suffixed(&[&r]),
+        );
+
+        r = chain(&[&l[repeat_point + 1..], &r]);
+        l.push(*sym);
+    }
+
+    Ok((Steps::join(steps), r))
+}
+
+/// The mirror image of `find_u`: given x and y, where y only uses
+/// letters in x, find v such that x ~ vyx. Returns the steps to go
+/// from x to vyx, and v. Fails if y contains a letter not in x.
+pub fn find_v(x: WordRef, y: WordRef) -> Result<(Steps, Word), ReduceError> {
+    let mut xr = x.to_vec();
+    xr.reverse();
+    let mut yr = y.to_vec();
+    yr.reverse();
+    let (steps, mut ur) = find_u(&xr, &yr)?;
+    ur.reverse();
+    Ok((steps.word_rev(), ur))
+}
+
+// Convert a string from LMR to LR. Doesn't eliminate overlap between
+// L and R.
+fn remove_middle(l: WordRef, m: WordRef, r: WordRef) -> Steps {
+    // L and R both use all the letters, so these can't fail.
+    // Choose u s.t. L ~ LMRu
+    let (l_to_lmru, u) = &find_u(l, &chain(&[m, r])).unwrap();
+    let lmru_to_l = l_to_lmru.time_rev();
+    // Choose v s.t. R ~ vLR
+    let (r_to_vlr, v) = &find_v(r, l).unwrap();
+    let vlr_to_r = r_to_vlr.time_rev();
+
+    Steps::join(vec![
+        // LM(R) -> LM(vLR)
+        Steps::prefix(&[l, m], r_to_vlr),
+        //   LMv(LR) -> LMv(LRLR)
+        Steps::square(&[l, r]).into_prefixed(&[l, m, v]),
+        // LM(vLR)LR -> LM(R)LR
+        vlr_to_r.into_suffixed(&[l, r]).into_prefixed(&[l, m]),
+        // LMR(L)R -> LMR(LMRu)R
+        l_to_lmru.suffix(&[r]).into_prefixed(&[l, m, r]),
+        // (LMRLMR)uR -> (LMR)uR
+        Steps::square(&[l, m, r])
+            .into_suffixed(&[u, r])
+            .into_time_rev(),
+        // (LMRu)R -> LR
+        lmru_to_l.into_suffixed(&[r]),
+    ])
+}
@@ -0,0 +1,166 @@
+////////////////////////////////////////////////////////////////////////
+// Rendering of reduction steps in other formats.
+//
+
+use std::collections::HashMap;
+
+use crate::{word_to_str, Alphabet, Op, Steps};
+
+// Write a side of a step in LaTeX, marking the factor with an
+// underbrace.
+fn step_to_latex(pre: &str, factor: &str, post: &str) -> String {
+    format!("{}\\underbrace{{{}}}{}", pre, factor, post)
+}
+
+// ANSI escape sequences for terminal output.
+const ANSI_DIM: &str = "\x1b[2m";
+const ANSI_HIGHLIGHT: &str = "\x1b[1;33m";
+const ANSI_RESET: &str = "\x1b[0m";
+
+// Wrap text in an ANSI escape sequence, unless it's empty.
+fn paint(code: &str, s: &str) -> String {
+    if s.is_empty() {
+        String::new()
+    } else {
+        format!("{}{}{}", code, s, ANSI_RESET)
+    }
+}
+
+// Write a side of a step with the parenthesised factor highlighted,
+// and the rest dimmed.
+fn step_to_ansi(pre: &str, factor: &str, post: &str) -> String {
+    format!(
+        "{}{}{}",
+        paint(ANSI_DIM, pre),
+        paint(ANSI_HIGHLIGHT, &format!("({})", factor)),
+        paint(ANSI_DIM, post)
+    )
+}
+
+// Show a word as Markdown code, or say if it's empty, as an empty
+// code span doesn't render.
+fn word_to_markdown(s: &str) -> String {
+    if s.is_empty() {
+        String::from("the empty word")
+    } else {
+        format!("`{}`", s)
+    }
+}
+
+impl Steps {
+    /// Render the steps as a LaTeX `align*` environment, one step per
+    /// line, with the factor being squared or unsquared underbraced.
+    pub fn to_latex(&self) -> String {
+        let lines = if self.num_operations() == 0 {
+            vec![word_to_str(self.start())]
+        } else {
+            self.marked_steps(&Alphabet::lowercase(), step_to_latex)
+                .iter()
+                .map(|(l, r)| format!("{} &\\to {}", l, r))
+                .collect::<Vec<_>>()
+        };
+
+        let mut res = String::from("\\begin{align*}\n");
+        res.push_str(&lines.join(" \\\\\n"));
+        res.push_str("\n\\end{align*}\n");
+        res
+    }
+
+    /// Render the steps as Markdown: a line giving the start and end
+    /// words, then a numbered list of the steps, with the factor being
+    /// squared or unsquared still in parentheses.
+    pub fn to_markdown(&self) -> String {
+        let mut res = format!(
+            "Reducing {} to {}:\n\n",
+            word_to_markdown(&word_to_str(self.start())),
+            word_to_markdown(&word_to_str(self.end()))
+        );
+        for (idx, (l, r)) in self.steps().iter().enumerate() {
+            res.push_str(&format!("{}. `{}` → `{}`\n", idx + 1, l, r));
+        }
+        res
+    }
+
+    /// Render the steps as `Display` does, but coloured for a
+    /// terminal: the factor being squared or unsquared is highlighted,
+    /// and the unchanged rest of the word dimmed.
+    pub fn to_ansi(&self) -> String {
+        self.to_ansi_with(&Alphabet::lowercase())
+    }
+
+    /// Like `to_ansi`, but writing the words with the given alphabet.
+    pub fn to_ansi_with(&self, alphabet: &Alphabet) -> String {
+        self.marked_steps(alphabet, step_to_ansi)
+            .iter()
+            .map(|(l, r)| format!("{} -> {}\n", l, r))
+            .collect()
+    }
+
+    /// Describe the steps in English, a sentence per step, saying
+    /// which factor is squared or unsquared and what that gives.
+    pub fn explain(&self) -> String {
+        self.explain_with(&Alphabet::lowercase())
+    }
+
+    /// Like `explain`, but writing the words with the given alphabet.
+    pub fn explain_with(&self, alphabet: &Alphabet) -> String {
+        let show = |word: &[u8]| word_to_markdown(&alphabet.encode(word));
+        let mut res = format!("Start with {}.\n", show(self.start()));
+        for (idx, (before, after, op)) in self.operations().iter().enumerate() {
+            let sentence = match op {
+                Op::Square { at, len } => format!(
+                    "Square {} at letter {}",
+                    show(&before[*at..at + len]),
+                    at + 1
+                ),
+                Op::Unsquare { at, len } => format!(
+                    "Remove the repeat of {} at letter {}",
+                    show(&before[*at..at + len]),
+                    at + 1
+                ),
+            };
+            res.push_str(&format!(
+                "{}. {}, giving {}.\n",
+                idx + 1,
+                sentence,
+                show(after)
+            ));
+        }
+        if self.num_operations() == 0 {
+            res.push_str("There's nothing to do.\n");
+        } else {
+            res.push_str(&format!("That leaves {}.\n", show(self.end())));
+        }
+        res
+    }
+
+    /// Render the steps as a Graphviz DOT digraph, with a node per
+    /// distinct intermediate word and edges labelled with whether the
+    /// step squares or unsquares.
+    pub fn to_dot(&self) -> String {
+        let mut nodes: HashMap<String, usize> = HashMap::new();
+        let mut res = String::from("digraph steps {\n");
+
+        let mut node_for = |word: String, res: &mut String| -> usize {
+            let next = nodes.len();
+            *nodes.entry(word.clone()).or_insert_with(|| {
+                res.push_str(&format!("    n{} [label=\"{}\"];\n", next, word));
+                next
+            })
+        };
+
+        let mut prev = node_for(word_to_str(self.start()), &mut res);
+        for (_, after, op) in self.operations() {
+            let next = node_for(word_to_str(&after), &mut res);
+            let op = match op {
+                Op::Square { .. } => "square",
+                Op::Unsquare { .. } => "unsquare",
+            };
+            res.push_str(&format!("    n{} -> n{} [label=\"{}\"];\n", prev, next, op));
+            prev = next;
+        }
+
+        res.push_str("}\n");
+        res
+    }
+}
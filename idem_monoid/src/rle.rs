@@ -0,0 +1,95 @@
+////////////////////////////////////////////////////////////////////////
+// Run-length encoded words, such as "a4b4" for "aaaabbbb".
+//
+
+use std::error::Error;
+use std::fmt;
+
+use crate::{try_str_to_word, word_to_str, ReduceError, Word, WordRef};
+
+/// Reasons a run-length encoded word can't be parsed.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ParseError {
+    /// A character that isn't a lower-case letter.
+    InvalidChar(char),
+    /// A count that doesn't follow a letter, at the given character
+    /// position.
+    CountWithoutLetter(usize),
+    /// A count too big to represent.
+    CountTooLarge(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidChar(c) => {
+                write!(f, "invalid character '{}', expected 'a' to 'z'", c)
+            }
+            ParseError::CountWithoutLetter(pos) => {
+                write!(f, "count at position {} doesn't follow a letter", pos)
+            }
+            ParseError::CountTooLarge(count) => write!(f, "count {} is too large", count),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Expand run-length encoded text, where each character may be
+/// followed by a count of how many times it repeats, so "x3yz2" is
+/// "xxxyzz". Characters without a count appear once. Any non-digit
+/// character is allowed, so this works with any alphabet.
+pub fn expand_rle(s: &str) -> Result<String, ParseError> {
+    let mut res = String::new();
+    let mut chars = s.chars().enumerate().peekable();
+    while let Some((pos, c)) = chars.next() {
+        if c.is_ascii_digit() {
+            return Err(ParseError::CountWithoutLetter(pos));
+        }
+        let mut count = String::new();
+        while let Some((_, d)) = chars.next_if(|(_, d)| d.is_ascii_digit()) {
+            count.push(d);
+        }
+        let repeats = if count.is_empty() {
+            1
+        } else {
+            count
+                .parse::<usize>()
+                .map_err(|_| ParseError::CountTooLarge(count.clone()))?
+        };
+        res.extend(std::iter::repeat_n(c, repeats));
+    }
+    Ok(res)
+}
+
+/// Run-length encode text, the reverse of `expand_rle`. Runs of a
+/// single character are left as just the character.
+pub fn compress_rle(s: &str) -> String {
+    let mut res = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        let mut count = 1;
+        while chars.next_if_eq(&c).is_some() {
+            count += 1;
+        }
+        res.push(c);
+        if count > 1 {
+            res.push_str(&count.to_string());
+        }
+    }
+    res
+}
+
+/// Parse a run-length encoded word over 'a' to 'z', such as "a4b4".
+pub fn parse_rle(s: &str) -> Result<Word, ParseError> {
+    try_str_to_word(&expand_rle(s)?).map_err(|e| match e {
+        ReduceError::InvalidChar(c) => ParseError::InvalidChar(c),
+        // Characters are all that's checked.
+        _ => unreachable!(),
+    })
+}
+
+/// Write a word in run-length encoded form.
+pub fn word_to_rle(word: WordRef) -> String {
+    compress_rle(&word_to_str(word))
+}
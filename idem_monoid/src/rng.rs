@@ -0,0 +1,46 @@
+////////////////////////////////////////////////////////////////////////
+// A small, seedable pseudo-random number generator, so that sampled
+// checks are reproducible without pulling in a dependency.
+//
+
+use crate::{Sym, Word};
+
+// SplitMix64, as described by Steele, Lea and Flood.
+pub(crate) struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number in 0..n. Slightly biased, but fine for our purposes.
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+/// Generate `n_words` random words of length `len` over the first
+/// `n_letters` letters. The same seed always gives the same words.
+/// Panics if `n_letters` is 0 and `len` isn't, as there are no
+/// letters to build the words from.
+pub fn random_words(n_words: usize, len: usize, n_letters: usize, seed: u64) -> Vec<Word> {
+    assert!(
+        n_letters > 0 || len == 0,
+        "Can't build words of length {} from no letters",
+        len
+    );
+    let mut rng = Rng::new(seed);
+    (0..n_words)
+        .map(|_| (0..len).map(|_| rng.below(n_letters) as Sym).collect())
+        .collect()
+}
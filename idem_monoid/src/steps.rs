@@ -0,0 +1,505 @@
+////////////////////////////////////////////////////////////////////////
+// Structure to represent a sequence of squaring/unsquaring
+// steps. Intended to make it impossible (when using the interface) to
+// generate invalid sequences of operations.
+//
+
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::try_str_to_word;
+use crate::{chain, word_to_str, Alphabet, Word, WordRef};
+
+/// A sequence of steps to go from a word to another representation
+/// of it. It tries to encapsulate the steps to make sure we don't
+/// accidentally mis-step.
+///
+/// Each step squares or unsquares a factor of the word. Steps are
+/// written as strings in the usual 'a' = 0 alphabet, with the factor
+/// being operated on in parentheses.
+#[derive(Clone, Debug)]
+pub struct Steps {
+    start: Word,
+    end: Word,
+    // The operations, each applying to the word the previous one
+    // left, starting from 'start' and finishing at 'end'. The
+    // intermediate words and step strings are rebuilt from these.
+    ops: Vec<Op>,
+}
+
+/// A plain version of `Steps` that can be serialised, with the words
+/// written as strings. Use `Steps::to_serializable` and
+/// `Steps::from_serializable` to convert.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SerializableSteps {
+    pub start: String,
+    pub end: String,
+    pub steps: Vec<(String, String)>,
+}
+
+/// A single squaring or unsquaring. `at` is where the factor starts,
+/// and `len` is the length of a single copy of it, so squaring turns
+/// the `len` letters from `at` into two copies, and unsquaring turns
+/// two copies back into one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Op {
+    Square { at: usize, len: usize },
+    Unsquare { at: usize, len: usize },
+}
+
+impl Op {
+    // Where the factor starts, and how many letters it covers before
+    // and after the operation.
+    fn spans(self) -> (usize, usize, usize) {
+        match self {
+            Op::Square { at, len } => (at, len, 2 * len),
+            Op::Unsquare { at, len } => (at, 2 * len, len),
+        }
+    }
+
+    // The operation that undoes this one.
+    fn inverse(self) -> Op {
+        match self {
+            Op::Square { at, len } => Op::Unsquare { at, len },
+            Op::Unsquare { at, len } => Op::Square { at, len },
+        }
+    }
+
+    // The same operation, with the factor starting elsewhere.
+    fn moved_to(self, at: usize) -> Op {
+        match self {
+            Op::Square { len, .. } => Op::Square { at, len },
+            Op::Unsquare { len, .. } => Op::Unsquare { at, len },
+        }
+    }
+}
+
+// Write a word with the 'span' letters from 'at' picked out by
+// 'mark', which is given the letters before, in and after the factor.
+fn mark_factor<F>(alphabet: &Alphabet, word: WordRef, at: usize, span: usize, mark: &F) -> String
+where
+    F: Fn(&str, &str, &str) -> String,
+{
+    mark(
+        &alphabet.encode(&word[..at]),
+        &alphabet.encode(&word[at..at + span]),
+        &alphabet.encode(&word[at + span..]),
+    )
+}
+
+// Split a step string into its word, and the operation's factor:
+// where it starts, and its length. Fails unless there's exactly one
+// non-empty parenthesised factor.
+#[cfg(feature = "serde")]
+fn parse_step(s: &str) -> Result<(Word, usize, usize), String> {
+    let malformed = || format!("Step '{}' doesn't have exactly one (factor)", s);
+    let (prefix, rest) = s.split_once('(').ok_or_else(malformed)?;
+    let (factor, suffix) = rest.split_once(')').ok_or_else(malformed)?;
+    if factor.is_empty()
+        || [prefix, factor, suffix]
+            .iter()
+            .any(|part| part.contains(['(', ')']))
+    {
+        return Err(malformed());
+    }
+    let parse =
+        |w: &str| try_str_to_word(w).map_err(|e| format!("Can't parse step '{}': {}", s, e));
+    let (prefix, factor, suffix) = (parse(prefix)?, parse(factor)?, parse(suffix)?);
+    Ok((
+        chain(&[&prefix, &factor, &suffix]),
+        prefix.len(),
+        factor.len(),
+    ))
+}
+
+// Apply the operation to the word, or None if it doesn't fit: the
+// factor runs off the end, or what's unsquared isn't a square.
+fn apply_op(word: WordRef, op: Op) -> Option<Word> {
+    match op {
+        Op::Square { at, len } if at + len <= word.len() => {
+            Some(chain(&[&word[..at + len], &word[at..]]))
+        }
+        Op::Unsquare { at, len }
+            if at + 2 * len <= word.len() && word[at..at + len] == word[at + len..at + 2 * len] =>
+        {
+            Some(chain(&[&word[..at + len], &word[at + 2 * len..]]))
+        }
+        _ => None,
+    }
+}
+
+// Recover a step's operation from its before and after strings,
+// returning the before word and the operation. Fails unless both mark
+// the same factor, and the after word is the before word with that
+// factor squared or unsquared.
+#[cfg(feature = "serde")]
+fn parse_op(l: &str, r: &str) -> Result<(Word, Op), String> {
+    let (before, at, before_len) = parse_step(l)?;
+    let (after, after_at, after_len) = parse_step(r)?;
+    let op = if after_len == 2 * before_len {
+        Some(Op::Square {
+            at,
+            len: before_len,
+        })
+    } else if before_len == 2 * after_len {
+        Some(Op::Unsquare { at, len: after_len })
+    } else {
+        None
+    };
+    match op.filter(|op| at == after_at && apply_op(&before, *op).as_ref() == Some(&after)) {
+        Some(op) => Ok((before, op)),
+        None => Err(format!(
+            "Step ({} -> {}) neither squares nor unsquares its factor",
+            l, r
+        )),
+    }
+}
+
+impl fmt::Display for Steps {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write_with(f, &Alphabet::lowercase())
+    }
+}
+
+impl Steps {
+    /// The word the steps start from.
+    pub fn start(&self) -> &Word {
+        &self.start
+    }
+
+    /// The word the steps finish at.
+    pub fn end(&self) -> &Word {
+        &self.end
+    }
+
+    /// The (before, after) strings for each step, with the factor
+    /// operated on in parentheses.
+    pub fn steps(&self) -> Vec<(String, String)> {
+        self.steps_with(&Alphabet::lowercase())
+    }
+
+    /// Like `steps`, but writing the words with the given alphabet.
+    pub fn steps_with(&self, alphabet: &Alphabet) -> Vec<(String, String)> {
+        self.marked_steps(alphabet, |pre, factor, post| {
+            format!("{}({}){}", pre, factor, post)
+        })
+    }
+
+    // The (before, after) strings for each step, with 'mark' given
+    // the letters before, in and after the factor operated on, to
+    // write it out.
+    pub(crate) fn marked_steps<F>(&self, alphabet: &Alphabet, mark: F) -> Vec<(String, String)>
+    where
+        F: Fn(&str, &str, &str) -> String,
+    {
+        self.operations()
+            .into_iter()
+            .map(|(before, after, op)| {
+                let (at, before_span, after_span) = op.spans();
+                (
+                    mark_factor(alphabet, &before, at, before_span, &mark),
+                    mark_factor(alphabet, &after, at, after_span, &mark),
+                )
+            })
+            .collect()
+    }
+
+    /// Write the steps out as `Display` does, but writing the words
+    /// with the given alphabet.
+    pub fn to_string_with(&self, alphabet: &Alphabet) -> String {
+        let mut res = String::new();
+        self.write_with(&mut res, alphabet).unwrap();
+        res
+    }
+
+    // Write the steps out a line per step, as "before -> after".
+    fn write_with<W: fmt::Write>(&self, out: &mut W, alphabet: &Alphabet) -> fmt::Result {
+        for (l, r) in self.steps_with(alphabet) {
+            writeln!(out, "{} -> {}", l, r)?;
+        }
+        Ok(())
+    }
+
+    /// Number of squaring and unsquaring operations performed.
+    pub fn num_operations(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Number of squaring operations performed.
+    pub fn num_squares(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op, Op::Square { .. }))
+            .count()
+    }
+
+    /// Number of unsquaring operations performed.
+    pub fn num_unsquares(&self) -> usize {
+        self.ops
+            .iter()
+            .filter(|op| matches!(op, Op::Unsquare { .. }))
+            .count()
+    }
+
+    /// Length of the longest word passed through, including the start
+    /// and end words.
+    pub fn max_intermediate_len(&self) -> usize {
+        let mut len = self.start.len();
+        let mut max_len = len.max(self.end.len());
+        for op in self.ops.iter() {
+            let (_, before_span, after_span) = op.spans();
+            len = len + after_span - before_span;
+            max_len = max_len.max(len);
+        }
+        max_len
+    }
+
+    /// Does some intermediate word get longer than both the start and
+    /// end words? Reductions often have to lengthen a word before it
+    /// can be shortened.
+    pub fn peaked(&self) -> bool {
+        self.max_intermediate_len() > self.start.len().max(self.end.len())
+    }
+
+    /// No-op
+    pub fn empty(w: WordRef) -> Steps {
+        Steps {
+            start: w.to_vec(),
+            end: w.to_vec(),
+            ops: Vec::new(),
+        }
+    }
+
+    /// Represents a step from w to ww, where w is the concatenation
+    /// of the given words.
+    pub fn square(m: &[WordRef]) -> Steps {
+        let mw = chain(m);
+        let m2w = chain(&[&mw, &mw]);
+        let len = mw.len();
+
+        Steps {
+            start: mw,
+            end: m2w,
+            ops: vec![Op::Square { at: 0, len }],
+        }
+    }
+
+    /// Perform the steps one after the other. Panics if the list is
+    /// empty, or one entry doesn't start where the previous one
+    /// ended.
+    pub fn join(list: Vec<Steps>) -> Steps {
+        let start = list.first().unwrap().start.clone();
+        let mut end = start.clone();
+        let mut ops = Vec::new();
+
+        for mut step in list.into_iter() {
+            assert_eq!(end, step.start);
+            ops.append(&mut step.ops);
+            end = step.end;
+        }
+
+        Steps { start, end, ops }
+    }
+
+    /// Perform the steps with the given words stuck on the front.
+    // Written this way so we can use it in prefix form
+    pub fn prefix(words: &[WordRef], s: &Steps) -> Steps {
+        s.clone().into_prefixed(words)
+    }
+
+    /// Perform the steps with the given words stuck on the end.
+    pub fn suffix(&self, words: &[WordRef]) -> Steps {
+        self.clone().into_suffixed(words)
+    }
+
+    /// Like `prefix`, but consumes the steps, reusing their storage
+    /// rather than allocating new words.
+    pub fn into_prefixed(mut self, words: &[WordRef]) -> Steps {
+        let word = chain(words);
+        self.start.splice(0..0, word.iter().copied());
+        self.end.splice(0..0, word.iter().copied());
+        for op in self.ops.iter_mut() {
+            let (at, _, _) = op.spans();
+            *op = op.moved_to(at + word.len());
+        }
+        self
+    }
+
+    /// Like `suffix`, but consumes the steps, reusing their storage
+    /// rather than allocating new words. The operations don't move.
+    pub fn into_suffixed(mut self, words: &[WordRef]) -> Steps {
+        let word = chain(words);
+        self.start.extend(&word);
+        self.end.extend(&word);
+        self
+    }
+
+    /// Generate steps for the reverse operation.
+    pub fn time_rev(&self) -> Steps {
+        self.clone().into_time_rev()
+    }
+
+    /// Like `time_rev`, but consumes the steps, reusing their storage.
+    pub fn into_time_rev(mut self) -> Steps {
+        std::mem::swap(&mut self.start, &mut self.end);
+        self.ops.reverse();
+        for op in self.ops.iter_mut() {
+            *op = op.inverse();
+        }
+        self
+    }
+
+    /// Generate steps for the word written backwards.
+    pub fn word_rev(&self) -> Steps {
+        // A factor starting at 'at' in a word of length 'len' ends
+        // 'len - at - span' letters from the end.
+        let mut len = self.start.len();
+        let ops = self
+            .ops
+            .iter()
+            .map(|op| {
+                let (at, before_span, after_span) = op.spans();
+                let rev_at = len - at - before_span;
+                len = len + after_span - before_span;
+                op.moved_to(rev_at)
+            })
+            .collect::<Vec<_>>();
+
+        Steps {
+            start: self.start.iter().rev().copied().collect::<Vec<_>>(),
+            end: self.end.iter().rev().copied().collect::<Vec<_>>(),
+            ops,
+        }
+    }
+
+    /// Convert to a form that can be serialised.
+    #[cfg(feature = "serde")]
+    pub fn to_serializable(&self) -> SerializableSteps {
+        SerializableSteps {
+            start: word_to_str(&self.start),
+            end: word_to_str(&self.end),
+            steps: self.steps(),
+        }
+    }
+
+    /// Convert back from the serialisable form, checking the steps are
+    /// valid, as they may have come from anywhere.
+    #[cfg(feature = "serde")]
+    pub fn from_serializable(s: SerializableSteps) -> Result<Steps, String> {
+        let parse =
+            |w: &str| try_str_to_word(w).map_err(|e| format!("Can't parse word '{}': {}", w, e));
+        let start = parse(&s.start)?;
+        let mut current = start.clone();
+        let mut ops = Vec::new();
+        for (idx, (l, r)) in s.steps.iter().enumerate() {
+            let (before, op) = parse_op(l, r).map_err(|e| format!("Step {}: {}", idx, e))?;
+            if before != current {
+                return Err(format!(
+                    "Step {} starts at {}, but previous step ended at {}",
+                    idx,
+                    word_to_str(&before),
+                    word_to_str(&current)
+                ));
+            }
+            current = apply_op(&current, op).unwrap();
+            ops.push(op);
+        }
+        let steps = Steps {
+            start,
+            end: parse(&s.end)?,
+            ops,
+        };
+        steps.verify()?;
+        Ok(steps)
+    }
+
+    /// Follow the letter at the given position of the start word
+    /// through the steps, returning its position in the end word. When
+    /// a factor is squared, its letters stay in the first copy. When
+    /// one is unsquared, letters in the first copy survive, and those
+    /// in the second are absorbed, giving None.
+    pub fn track_position(&self, pos: usize) -> Option<usize> {
+        assert!(pos < self.start.len(), "Position outside the word");
+        let mut pos = pos;
+        for op in self.ops.iter() {
+            let (at, before_span, after_span) = op.spans();
+            if pos >= at + before_span {
+                // After the factor, so just shifted.
+                pos = pos + after_span - before_span;
+            } else if pos >= at + after_span {
+                // In the second copy of an unsquared factor.
+                return None;
+            }
+        }
+        Some(pos)
+    }
+
+    /// The steps as (before, after, operation) triples, replaying the
+    /// operations from the start word.
+    pub fn operations(&self) -> Vec<(Word, Word, Op)> {
+        let mut current = self.start.clone();
+        self.ops
+            .iter()
+            .map(|op| {
+                let after = apply_op(&current, *op).expect("Operation doesn't fit the word");
+                let before = std::mem::replace(&mut current, after.clone());
+                (before, after, *op)
+            })
+            .collect()
+    }
+
+    /// Remove pairs of adjacent steps that cancel out, where the
+    /// second step goes back to the word the first started from.
+    /// Removing a pair can leave another pair next to each other, so
+    /// these are removed too. The start and end are unchanged.
+    pub fn simplify(&self) -> Steps {
+        // The operations kept so far, with the word each starts from.
+        let mut kept: Vec<(Word, Op)> = Vec::new();
+        for (before, after, op) in self.operations() {
+            match kept.last() {
+                Some((prev_before, _)) if *prev_before == after => {
+                    kept.pop();
+                }
+                _ => kept.push((before, op)),
+            }
+        }
+
+        let simplified = Steps {
+            start: self.start.clone(),
+            end: self.end.clone(),
+            ops: kept.into_iter().map(|(_, op)| op).collect(),
+        };
+        debug_assert_eq!(simplified.verify(), Ok(()));
+        simplified
+    }
+
+    /// Check the steps are valid: each operation's factor fits in the
+    /// word so far, anything unsquared is a square, and the whole lot
+    /// goes from `start` to `end`.
+    pub fn verify(&self) -> Result<(), String> {
+        let mut current = self.start.clone();
+        for (idx, op) in self.ops.iter().enumerate() {
+            current = apply_op(&current, *op).ok_or_else(|| {
+                format!(
+                    "Step {} ({:?}) doesn't fit the word {}",
+                    idx,
+                    op,
+                    word_to_str(&current)
+                )
+            })?;
+        }
+
+        if current != self.end {
+            return Err(format!(
+                "Steps finish at {}, but should end at {}",
+                word_to_str(&current),
+                word_to_str(&self.end)
+            ));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+////////////////////////////////////////////////////////////////////////
+// The structure of the monoid as a whole.
+//
+
+use crate::{generate_monoid, product, reduce, ConcatMode, Sym, Word, WordRef};
+
+/// The right Cayley graph of the monoid over n letters, as a list of
+/// (x, g, xg) edges, where x is an element, g a generator, and xg the
+/// reduced product. Elements are in generation order, and edges from
+/// each element in order of generator.
+pub fn cayley_graph(n_letters: usize) -> Vec<(Word, Sym, Word)> {
+    let mut edges = Vec::new();
+    for x in generate_monoid(n_letters) {
+        for g in 0..n_letters as Sym {
+            let xg = reduce(&product(&x, &[g], ConcatMode::Raw)).end().clone();
+            edges.push((x.clone(), g, xg));
+        }
+    }
+    edges
+}
+
+/// Is the word equivalent to the identity? Reduction never changes
+/// which letters a word uses, so only the empty word is.
+pub fn is_identity(w: WordRef) -> bool {
+    w.is_empty()
+}
+
+/// The elements of a monoid with special roles with respect to
+/// multiplication.
+pub struct SpecialElements {
+    /// Elements e with ew = we = w for all w.
+    pub identities: Vec<Word>,
+    /// Elements z with zw = z for all w.
+    pub left_zeros: Vec<Word>,
+    /// Elements z with wz = z for all w.
+    pub right_zeros: Vec<Word>,
+}
+
+/// Find the identities and left and right zeros of the monoid over n
+/// letters, by multiplying elements together. This takes time
+/// quadratic in the size of the monoid, so is only practical for up
+/// to 3 letters.
+pub fn special_elements(n_letters: usize) -> SpecialElements {
+    let elements = generate_monoid(n_letters);
+    let times = |x: &Word, y: &Word| reduce(&product(x, y, ConcatMode::Raw)).end().clone();
+
+    let mut special = SpecialElements {
+        identities: Vec::new(),
+        left_zeros: Vec::new(),
+        right_zeros: Vec::new(),
+    };
+    for x in elements.iter() {
+        if elements
+            .iter()
+            .all(|w| times(x, w) == *w && times(w, x) == *w)
+        {
+            special.identities.push(x.clone());
+        }
+        if elements.iter().all(|w| times(x, w) == *x) {
+            special.left_zeros.push(x.clone());
+        }
+        if elements.iter().all(|w| times(w, x) == *x) {
+            special.right_zeros.push(x.clone());
+        }
+    }
+    special
+}
+
+/// Find the pairs of distinct elements x and y of the monoid over n
+/// letters with xy = yx, leaving out the identity, which commutes
+/// with everything. Each pair is listed once, with x before y in
+/// generation order. Like `special_elements`, this is quadratic in the
+/// size of the monoid.
+pub fn commuting_pairs(n_letters: usize) -> Vec<(Word, Word)> {
+    let elements = generate_monoid(n_letters);
+    let times = |x: &Word, y: &Word| reduce(&product(x, y, ConcatMode::Raw)).end().clone();
+
+    let mut pairs = Vec::new();
+    for (idx, x) in elements.iter().enumerate() {
+        if is_identity(x) {
+            continue;
+        }
+        for y in elements[idx + 1..].iter() {
+            if times(x, y) == times(y, x) {
+                pairs.push((x.clone(), y.clone()));
+            }
+        }
+    }
+    pairs
+}
+
+/// The natural partial order on a band: a <= b iff ab = ba = a,
+/// computed by reducing the products. Equivalently, a = bab.
+///
+/// The weaker condition a = aba defines the J-preorder, which isn't
+/// a partial order: in the free band it just says a uses all the
+/// letters b does.
+pub fn leq(a: WordRef, b: WordRef) -> bool {
+    let a_red = reduce(a).end().clone();
+    *reduce(&product(a, b, ConcatMode::Raw)).end() == a_red
+        && *reduce(&product(b, a, ConcatMode::Raw)).end() == a_red
+}
+
+/// A D-class of the monoid, split into its R-classes and L-classes.
+/// In a band, each R-class meets each L-class of the same D-class in
+/// exactly one element, so the D-class forms a grid (the "egg-box").
+pub struct DClass {
+    /// Classes of elements x, y with xy = y and yx = x.
+    pub r_classes: Vec<Vec<Word>>,
+    /// Classes of elements x, y with xy = x and yx = y.
+    pub l_classes: Vec<Vec<Word>>,
+}
+
+impl DClass {
+    /// The element in both the given R-class and L-class.
+    pub fn cell(&self, r: usize, l: usize) -> &Word {
+        self.r_classes[r]
+            .iter()
+            .find(|w| self.l_classes[l].contains(w))
+            .expect("R-class and L-class don't meet")
+    }
+}
+
+// Split the words into classes of the given equivalence relation, in
+// order of first appearance.
+fn partition(words: &[Word], related: impl Fn(&Word, &Word) -> bool) -> Vec<Vec<Word>> {
+    let mut classes: Vec<Vec<Word>> = Vec::new();
+    for w in words.iter() {
+        match classes.iter_mut().find(|class| related(&class[0], w)) {
+            Some(class) => class.push(w.clone()),
+            None => classes.push(vec![w.clone()]),
+        }
+    }
+    classes
+}
+
+/// Find the structure of Green's relations on the monoid over n
+/// letters, as a list of D-classes, in order of generation. In a band,
+/// x D y iff xyx = x and yxy = y, which happens iff x and y use the
+/// same letters. Relations are checked by reducing products, taking
+/// time quadratic in the size of the D-classes, so this is only
+/// practical for up to 3 letters.
+pub fn green_classes(n_letters: usize) -> Vec<DClass> {
+    let times = |x: &Word, y: &Word| reduce(&product(x, y, ConcatMode::Raw)).end().clone();
+
+    let d_classes = partition(&generate_monoid(n_letters), |x, y| {
+        times(&times(x, y), x) == *x && times(&times(y, x), y) == *y
+    });
+    d_classes
+        .iter()
+        .map(|d| DClass {
+            r_classes: partition(d, |x, y| times(x, y) == *y && times(y, x) == *x),
+            l_classes: partition(d, |x, y| times(x, y) == *x && times(y, x) == *y),
+        })
+        .collect()
+}
@@ -0,0 +1,86 @@
+//
+// Check the command line gives the same answers whichever way it
+// finds them.
+//
+
+#![cfg(feature = "serde")]
+
+use std::process::Command;
+
+// Run idem_monoid with the given arguments, returning its output.
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_idem_monoid"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+// Run idem_monoid with the given arguments, expecting it to fail, and
+// returning what it wrote to stderr.
+fn run_failing(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_idem_monoid"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    String::from_utf8(output.stderr).unwrap()
+}
+
+// Multiplying elements over 3 letters can be a table lookup, but not
+// when the steps are wanted.
+#[test]
+fn multiply_explains() {
+    let product = run(&["--multiply", "abc,cab"]);
+    assert_eq!(product, "abcab\n");
+    let explained = run(&["--multiply", "abc,cab", "--explain"]);
+    assert!(explained.starts_with("Start with `abccab`.\n"));
+    assert!(explained.ends_with("\nabcab\n"));
+    let counted = run(&["--multiply", "abc,cab", "--count-steps"]);
+    assert!(counted.contains("\n1 operation (0 squares, 1 unsquare)\n"));
+}
+
+// The tree's labels stay as they are, with only the words written in
+// the given alphabet.
+#[test]
+fn tree_with_alphabet() {
+    let tree = run(&["--alphabet", "xyz", "--reduce", "xyxy", "--tree"]);
+    assert_eq!(
+        tree,
+        "\
+xyxy -> xy
+  left: x -> x
+  right: y -> y
+  middle:
+    (xyxy) -> (xy)
+xy
+"
+    );
+}
+
+// Modes that work through every element are refused straight away
+// when there are too many elements, rather than running for ever.
+#[test]
+fn big_monoids_are_refused() {
+    for mode in [
+        "--check-closure",
+        "--check-associativity",
+        "--cayley",
+        "--structure",
+        "--green",
+        "--commuting",
+    ] {
+        let stderr = run_failing(&["--generators", "4", mode]);
+        assert!(stderr.contains("has 332381 elements"), "{}", mode);
+    }
+}
+
+// Only one mode can be picked at a time, rather than the first one
+// checked quietly winning.
+#[test]
+fn modes_conflict() {
+    let error = run_failing(&["--cayley", "--green"]);
+    assert!(error.contains("cannot be used with"));
+    run_failing(&["--reduce", "aa", "--multiply", "a,b"]);
+}
@@ -0,0 +1,148 @@
+//
+// Reductions of the smallest words, where the general algorithm is
+// most delicate.
+//
+
+use idem_monoid::{
+    power, reduce, str_to_word, try_reduce_left, try_reduce_right, MonoidElement, ReduceError,
+    Steps,
+};
+
+// Reduce a word given as a string, checking the steps are valid.
+fn reduce_str(s: &str) -> Steps {
+    let steps = reduce(&str_to_word(s));
+    steps.verify().unwrap();
+    steps
+}
+
+#[test]
+fn empty_word() {
+    let steps = reduce_str("");
+    assert!(steps.start().is_empty());
+    assert!(steps.end().is_empty());
+    assert_eq!(steps.num_operations(), 0);
+}
+
+#[test]
+fn single_letter() {
+    let steps = reduce_str("a");
+    assert_eq!(steps.end(), &str_to_word("a"));
+    assert_eq!(steps.num_operations(), 0);
+}
+
+#[test]
+fn letter_squared() {
+    let steps = reduce_str("aa");
+    assert_eq!(steps.end(), &str_to_word("a"));
+    assert_eq!(steps.steps(), &[("(aa)".to_string(), "(a)".to_string())]);
+}
+
+#[test]
+fn letter_power() {
+    let steps = reduce_str("aaaa");
+    assert_eq!(steps.end(), &str_to_word("a"));
+    assert_eq!(steps.num_unsquares(), 3);
+    assert_eq!(steps.num_squares(), 0);
+}
+
+#[test]
+fn other_letter_power() {
+    let steps = reduce_str("ccc");
+    assert_eq!(steps.end(), &str_to_word("c"));
+    assert_eq!(steps.num_operations(), 2);
+}
+
+#[test]
+fn word_power() {
+    let steps = power(&str_to_word("aba"), 4);
+    steps.verify().unwrap();
+    assert_eq!(steps.start(), &str_to_word("abaabaabaaba"));
+    assert_eq!(steps.end(), &str_to_word("aba"));
+    assert_eq!(steps.num_unsquares(), 3);
+}
+
+#[test]
+fn zeroth_power() {
+    let steps = power(&str_to_word("aba"), 0);
+    assert!(steps.start().is_empty());
+    assert!(steps.end().is_empty());
+}
+
+#[test]
+fn split_needs_enough_letters() {
+    let word = str_to_word("abab");
+    assert_eq!(
+        try_reduce_left(&word, 3).err(),
+        Some(ReduceError::WrongLetterCount(3, 2))
+    );
+    assert_eq!(
+        try_reduce_right(&word, 0).err(),
+        Some(ReduceError::WrongLetterCount(0, 2))
+    );
+    assert_eq!(
+        try_reduce_left(&str_to_word(""), 1).err(),
+        Some(ReduceError::WrongLetterCount(1, 0))
+    );
+}
+
+#[test]
+fn split_reduces_subword() {
+    // The longest prefix using one letter is "aa".
+    let steps = try_reduce_left(&str_to_word("aabab"), 2).unwrap();
+    steps.verify().unwrap();
+    assert_eq!(steps.end(), &str_to_word("abab"));
+
+    let steps = try_reduce_right(&str_to_word("ababcbcc"), 3).unwrap();
+    steps.verify().unwrap();
+    assert_eq!(steps.end(), &str_to_word("ababc"));
+}
+
+#[test]
+fn unsquaring_doesnt_peak() {
+    assert!(!reduce_str("abab").peaked());
+}
+
+#[test]
+fn removing_middle_peaks() {
+    // Removing the middle means squaring most of the word first.
+    assert!(reduce_str("abcbcacb").peaked());
+}
+
+#[test]
+fn simplify_drops_cancelling_steps() {
+    let steps = reduce_str("abaab");
+    let simplified = steps.simplify();
+    simplified.verify().unwrap();
+    assert_eq!(simplified.start(), steps.start());
+    assert_eq!(simplified.end(), steps.end());
+    assert!(simplified.num_operations() < steps.num_operations());
+}
+
+#[test]
+fn explain_letter_squared() {
+    assert_eq!(
+        reduce_str("aa").explain(),
+        "Start with `aa`.\n1. Remove the repeat of `a` at letter 1, giving `a`.\nThat leaves `a`.\n"
+    );
+}
+
+// Symbols beyond 'z' reduce just like small ones.
+#[test]
+fn wide_symbols_reduce() {
+    assert_eq!(reduce(&[0, 40, 0]).end(), &[0, 40, 0]);
+    assert_eq!(reduce(&[200, 255, 200, 255]).end(), &[200, 255]);
+}
+
+// Elements compare by the natural order: `aba` is below `a`, since
+// a.aba = aba.a = aba, while `a` and `b` are incomparable.
+#[test]
+fn elements_use_natural_order() {
+    let a = MonoidElement::new(&str_to_word("a"));
+    let b = MonoidElement::new(&str_to_word("b"));
+    let aba = &(&a * &b) * &a;
+    assert!(aba < a);
+    assert!(a > aba);
+    assert_eq!((&a * &b).partial_cmp(&a), None);
+    assert_eq!(a.partial_cmp(&b), None);
+    assert!(a <= a.clone());
+}
@@ -0,0 +1,22 @@
+//
+// The longest canonical words, for small numbers of generators.
+//
+
+use idem_monoid::{generate_monoid, max_canonical_len, str_to_word};
+
+#[test]
+fn max_lengths() {
+    let lengths = (0..=3).map(max_canonical_len).collect::<Vec<_>>();
+    assert_eq!(lengths, vec![0, 1, 3, 8]);
+}
+
+#[test]
+fn longest_on_three_letters() {
+    let longest = generate_monoid(3)
+        .into_iter()
+        .filter(|word| word.len() == 8)
+        .collect::<Vec<_>>();
+    // Two for each order the letters can first appear in.
+    assert_eq!(longest.len(), 12);
+    assert!(longest.contains(&str_to_word("abacbcac")));
+}
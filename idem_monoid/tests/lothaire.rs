@@ -0,0 +1,37 @@
+//
+// The core constructions from Lothaire, building a longer word
+// equivalent to x with y inserted.
+//
+
+use idem_monoid::{find_u, find_v, str_to_word, ReduceError};
+
+#[test]
+fn find_u_gives_xyu() {
+    for (x, y) in [("abc", "ba"), ("abcab", "cca"), ("ab", ""), ("a", "aaa")] {
+        let (x, y) = (str_to_word(x), str_to_word(y));
+        let (steps, u) = find_u(&x, &y).unwrap();
+        steps.verify().unwrap();
+        assert_eq!(steps.start(), &x);
+        assert_eq!(steps.end(), &[&x[..], &y, &u].concat());
+    }
+}
+
+#[test]
+fn find_v_gives_vyx() {
+    for (x, y) in [("abc", "ba"), ("abcab", "cca"), ("ab", ""), ("a", "aaa")] {
+        let (x, y) = (str_to_word(x), str_to_word(y));
+        let (steps, v) = find_v(&x, &y).unwrap();
+        steps.verify().unwrap();
+        assert_eq!(steps.start(), &x);
+        assert_eq!(steps.end(), &[&v[..], &y, &x].concat());
+    }
+}
+
+#[test]
+fn missing_letter() {
+    let (x, y) = (str_to_word("abab"), str_to_word("abc"));
+    assert_eq!(find_u(&x, &y).err(), Some(ReduceError::MissingSym(2)));
+    assert_eq!(find_v(&x, &y).err(), Some(ReduceError::MissingSym(2)));
+    assert_eq!(find_u(&[], &x).err(), Some(ReduceError::MissingSym(0)));
+    assert_eq!(find_v(&[], &x).err(), Some(ReduceError::MissingSym(1)));
+}
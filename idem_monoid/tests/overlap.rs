@@ -0,0 +1,48 @@
+//
+// Overlaps between words, as used to merge them.
+//
+
+use idem_monoid::{merge, overlap_len, str_to_word, word_to_str};
+
+// Merge two words given as strings, returning the overlap length and
+// the merged word.
+fn merge_str(left: &str, right: &str) -> (usize, String) {
+    let (left, right) = (str_to_word(left), str_to_word(right));
+    (
+        overlap_len(&left, &right),
+        word_to_str(&merge(&left, &right)),
+    )
+}
+
+#[test]
+fn disjoint_words() {
+    assert_eq!(merge_str("ab", "cd"), (0, String::from("abcd")));
+    // Sharing letters isn't enough, they have to line up.
+    assert_eq!(merge_str("abc", "ba"), (0, String::from("abcba")));
+}
+
+#[test]
+fn partial_overlap() {
+    assert_eq!(merge_str("ab", "ba"), (1, String::from("aba")));
+    assert_eq!(merge_str("ba", "ab"), (1, String::from("bab")));
+    assert_eq!(merge_str("abcab", "cabd"), (3, String::from("abcabd")));
+    // The longest overlap wins, not the first found.
+    assert_eq!(merge_str("ababa", "abac"), (3, String::from("ababac")));
+}
+
+#[test]
+fn full_overlap() {
+    // The right word is a suffix of the left.
+    assert_eq!(merge_str("abc", "bc"), (2, String::from("abc")));
+    // The left word is a prefix of the right.
+    assert_eq!(merge_str("ab", "abc"), (2, String::from("abc")));
+    // The words are equal.
+    assert_eq!(merge_str("abab", "abab"), (4, String::from("abab")));
+}
+
+#[test]
+fn empty_words() {
+    assert_eq!(merge_str("", ""), (0, String::new()));
+    assert_eq!(merge_str("", "ab"), (0, String::from("ab")));
+    assert_eq!(merge_str("ab", ""), (0, String::from("ab")));
+}
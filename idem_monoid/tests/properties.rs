@@ -0,0 +1,154 @@
+//
+// Property tests for word conversion and reduction.
+//
+
+use proptest::prelude::*;
+
+use std::collections::HashSet;
+
+use idem_monoid::{
+    content, is_square_free, overlap_len, reduce, reduce_tracked, reduce_word, str_to_word,
+    word_to_str, ContentMask, Op, Reducer, Sym, Word,
+};
+
+// Random words of up to the given length, over the first n letters.
+fn words(n_letters: Sym, max_len: usize) -> impl Strategy<Value = Word> {
+    prop::collection::vec(0..n_letters, 0..=max_len)
+}
+
+proptest! {
+    #[test]
+    fn str_round_trip(word in words(26, 30)) {
+        prop_assert_eq!(str_to_word(&word_to_str(&word)), word);
+    }
+
+    #[test]
+    fn content_mask_matches_hash_set(word in prop::collection::vec(any::<Sym>(), 0..=30)) {
+        let mask = ContentMask::of(&word);
+        let set = word.iter().copied().collect::<HashSet<_>>();
+        prop_assert_eq!(mask.len(), set.len());
+        for sym in 0..=Sym::MAX {
+            prop_assert_eq!(mask.contains(sym), set.contains(&sym));
+        }
+    }
+
+    // Reduction only looks at which symbols are equal, so moving the
+    // letters to the top of the symbol range changes nothing else.
+    #[test]
+    fn reduction_ignores_symbol_values(word in words(4, 40)) {
+        let wide = |w: &[Sym]| w.iter().map(|sym| Sym::MAX - 50 * sym).collect::<Word>();
+        let steps = reduce(&wide(&word));
+        prop_assert_eq!(steps.end(), &wide(reduce(&word).end()));
+    }
+
+    #[test]
+    fn reduction_is_valid(word in words(4, 40)) {
+        let steps = reduce(&word);
+        prop_assert_eq!(steps.start(), &word);
+        prop_assert_eq!(steps.verify(), Ok(()));
+    }
+
+    #[test]
+    fn reduction_doesnt_lengthen(word in words(4, 40)) {
+        prop_assert!(reduce(&word).end().len() <= word.len());
+    }
+
+    #[test]
+    fn reduction_preserves_content(word in words(4, 40)) {
+        prop_assert_eq!(content(reduce(&word).end()), content(&word));
+    }
+
+    #[test]
+    fn reducing_reduced_word_is_noop(word in words(4, 40)) {
+        let reduced = reduce(&word).end().clone();
+        let steps = reduce(&reduced);
+        prop_assert_eq!(steps.num_operations(), 0);
+        prop_assert_eq!(steps.end(), &reduced);
+    }
+
+    #[test]
+    fn reduce_word_matches_reduce(word in words(4, 40)) {
+        prop_assert_eq!(reduce_word(&word), reduce(&word).end().clone());
+    }
+
+    // Words sharing prefixes of a common base, each reduced twice, so
+    // the cache is hit both on whole words and on prefixes.
+    #[test]
+    fn reducer_matches_reduce(
+        base in words(4, 30),
+        tails in prop::collection::vec((0..=30usize, words(4, 10)), 1..8),
+    ) {
+        let corpus = tails
+            .iter()
+            .map(|(cut, tail)| [&base[..(*cut).min(base.len())], tail].concat())
+            .collect::<Vec<_>>();
+        let mut reducer = Reducer::new();
+        for word in corpus.iter().chain(corpus.iter()) {
+            prop_assert_eq!(reducer.reduce_cached(word), reduce(word).end().clone());
+        }
+    }
+
+    #[test]
+    fn operations_apply(word in words(4, 40)) {
+        let steps = reduce(&word);
+        let mut current = word.clone();
+        for (before, after, op) in steps.operations() {
+            prop_assert_eq!(&before, &current);
+            current = match op {
+                Op::Square { at, len } => {
+                    [&before[..at + len], &before[at..]].concat()
+                }
+                Op::Unsquare { at, len } => {
+                    prop_assert_eq!(&before[at..at + len], &before[at + len..at + 2 * len]);
+                    [&before[..at + len], &before[at + 2 * len..]].concat()
+                }
+            };
+            prop_assert_eq!(&current, &after);
+        }
+        prop_assert_eq!(&current, steps.end());
+    }
+
+    // Small alphabets, so that long overlaps are common.
+    #[test]
+    fn overlap_len_matches_naive_scan(left in words(2, 16), right in words(2, 16)) {
+        let naive = (0..=left.len().min(right.len()))
+            .rev()
+            .find(|len| left[left.len() - len..] == right[..*len])
+            .unwrap();
+        prop_assert_eq!(overlap_len(&left, &right), naive);
+    }
+
+    #[test]
+    fn square_free_means_no_squares(word in words(3, 12)) {
+        let has_square = (1..=word.len() / 2).any(|len| {
+            (0..=word.len() - 2 * len).any(|idx| word[idx..idx + len] == word[idx + len..idx + 2 * len])
+        });
+        prop_assert_eq!(is_square_free(&word), !has_square);
+    }
+
+    #[test]
+    fn normal_forms_are_square_free(word in words(4, 40)) {
+        prop_assert!(is_square_free(reduce(&word).end()));
+    }
+
+    #[test]
+    fn tracked_letters_survive(word in words(4, 40), pos in 0..40usize) {
+        prop_assume!(pos < word.len());
+        let (steps, tracked) = reduce_tracked(&word, pos);
+        if let Some(end_pos) = tracked {
+            prop_assert_eq!(steps.end()[end_pos], word[pos]);
+        }
+    }
+
+    // Shortest words are only unique for up to 3 letters, so the
+    // reduction of the mirror image is only the mirror image of the
+    // reduction there.
+    #[test]
+    fn reduction_is_mirror_symmetric(word in words(3, 40)) {
+        let mut reversed = word.clone();
+        reversed.reverse();
+        let mut expected = reduce(&word).end().clone();
+        expected.reverse();
+        prop_assert_eq!(reduce(&reversed).end().clone(), expected);
+    }
+}
@@ -0,0 +1,21 @@
+//
+// Random words, for sampled checks and benchmarks.
+//
+
+use idem_monoid::random_words;
+
+#[test]
+fn random_word_sizes() {
+    let words = random_words(5, 7, 3, 42);
+    assert_eq!(words.len(), 5);
+    assert!(words.iter().all(|word| word.len() == 7));
+    assert!(words.iter().flatten().all(|sym| *sym < 3));
+    // The empty word needs no letters.
+    assert_eq!(random_words(2, 0, 0, 42), vec![Vec::new(), Vec::new()]);
+}
+
+#[test]
+#[should_panic(expected = "from no letters")]
+fn random_words_need_letters() {
+    random_words(1, 3, 0, 42);
+}
@@ -0,0 +1,74 @@
+//
+// Round-tripping reductions through JSON.
+//
+
+#![cfg(feature = "serde")]
+
+use idem_monoid::{reduce, str_to_word, SerializableSteps, Steps};
+
+// Serialise the steps to JSON and back.
+fn round_trip(steps: &Steps) -> Result<Steps, String> {
+    let json = serde_json::to_string(&steps.to_serializable()).unwrap();
+    let parsed = serde_json::from_str::<SerializableSteps>(&json).unwrap();
+    Steps::from_serializable(parsed)
+}
+
+#[test]
+fn reductions_round_trip() {
+    for s in ["", "a", "abab", "abcbcacbacab", "bacbcabcab"] {
+        let steps = reduce(&str_to_word(s));
+        let loaded = round_trip(&steps).unwrap();
+        assert_eq!(loaded.start(), steps.start());
+        assert_eq!(loaded.end(), steps.end());
+        assert_eq!(loaded.steps(), steps.steps());
+    }
+}
+
+#[test]
+fn invalid_steps_rejected() {
+    let mut serializable = reduce(&str_to_word("abab")).to_serializable();
+    serializable.end = String::from("ba");
+    assert!(Steps::from_serializable(serializable).is_err());
+
+    let bad_step = SerializableSteps {
+        start: String::from("ab"),
+        end: String::from("abc"),
+        steps: vec![(String::from("(ab)"), String::from("(abc)"))],
+    };
+    assert!(Steps::from_serializable(bad_step).is_err());
+}
+
+// Each step has to mark the factor it squares or unsquares, and the
+// words either side have to match the marking.
+#[test]
+fn badly_marked_steps_rejected() {
+    for (l, r) in [
+        ("ab", "abab"),
+        ("(ab)", "abab"),
+        ("((ab))", "((abab))"),
+        ("(ab)(c)", "(abab)(c)"),
+        ("()ab", "()ab"),
+        ("(ab", "(abab"),
+        ("a(b)", "(abab)"),
+        ("(ab)", "(aba)b"),
+    ] {
+        let bad_step = SerializableSteps {
+            start: String::from("ab"),
+            end: String::from("abab"),
+            steps: vec![(String::from(l), String::from(r))],
+        };
+        assert!(
+            Steps::from_serializable(bad_step).is_err(),
+            "{} -> {}",
+            l,
+            r
+        );
+    }
+
+    let good_step = SerializableSteps {
+        start: String::from("ab"),
+        end: String::from("abab"),
+        steps: vec![(String::from("(ab)"), String::from("(abab)"))],
+    };
+    assert!(Steps::from_serializable(good_step).is_ok());
+}
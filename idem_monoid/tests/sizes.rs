@@ -0,0 +1,56 @@
+//
+// The sizes of the monoids on small numbers of generators, which are
+// known independently.
+//
+
+use std::collections::HashSet;
+
+use idem_monoid::{estimate_output, generate_monoid, monoid_size, multiplication_table};
+
+const KNOWN_SIZES: [usize; 4] = [1, 2, 7, 160];
+
+#[test]
+fn generated_sizes() {
+    for (n, size) in KNOWN_SIZES.iter().enumerate() {
+        let elements = generate_monoid(n);
+        assert_eq!(elements.len(), *size, "{} generators", n);
+        let distinct = elements.iter().collect::<HashSet<_>>();
+        assert_eq!(distinct.len(), *size, "{} generators", n);
+    }
+}
+
+#[test]
+fn counted_sizes() {
+    for (n, size) in KNOWN_SIZES.iter().enumerate() {
+        assert_eq!(monoid_size(n), Some(*size as u64), "{} generators", n);
+    }
+}
+
+// The estimate matches the exact count while there is one, and keeps
+// going well beyond it.
+#[test]
+fn estimated_sizes() {
+    for n in 0..=5 {
+        let (log_elements, _) = estimate_output(n);
+        let size = monoid_size(n).unwrap() as f64;
+        assert!(
+            (log_elements - size.log10()).abs() < 1e-9,
+            "{} generators",
+            n
+        );
+    }
+    assert_eq!(monoid_size(6), None);
+    let (log_elements, log_bytes) = estimate_output(26);
+    assert!(log_elements.is_finite() && log_bytes > log_elements);
+}
+
+#[test]
+fn table_sizes() {
+    for (n, size) in KNOWN_SIZES.iter().enumerate() {
+        let table = multiplication_table(n).unwrap();
+        assert_eq!(table.len(), *size, "{} generators", n);
+        assert!(table.iter().all(|row| row.len() == *size));
+    }
+    // Too big to build.
+    assert_eq!(multiplication_table(4), None);
+}
@@ -0,0 +1,56 @@
+//
+// Embedding words into a larger alphabet with `variants_on`.
+//
+
+use idem_monoid::{str_to_word, variants_on, word_to_str, Sym};
+
+// Run `variants_on` on words given as strings, giving the results as
+// strings.
+fn variants(words: &[&str], n_letters: usize) -> Vec<(String, Sym)> {
+    let words = words.iter().map(|s| str_to_word(s)).collect::<Vec<_>>();
+    variants_on(&words, n_letters)
+        .iter()
+        .map(|(word, sym)| (word_to_str(word), *sym))
+        .collect()
+}
+
+// Build the expected results.
+fn expected(pairs: &[(&str, Sym)]) -> Vec<(String, Sym)> {
+    pairs.iter().map(|(s, sym)| (s.to_string(), *sym)).collect()
+}
+
+#[test]
+fn two_letters() {
+    assert_eq!(variants(&["a"], 2), expected(&[("b", 0), ("a", 1)]));
+    assert_eq!(variants(&[""], 2), expected(&[("", 0), ("", 1)]));
+}
+
+#[test]
+fn three_letters() {
+    assert_eq!(
+        variants(&["ab", "ba"], 3),
+        expected(&[
+            ("bc", 0),
+            ("cb", 0),
+            ("ac", 1),
+            ("ca", 1),
+            ("ab", 2),
+            ("ba", 2),
+        ])
+    );
+    assert_eq!(
+        variants(&["aba"], 3),
+        expected(&[("bcb", 0), ("aca", 1), ("aba", 2)])
+    );
+}
+
+#[test]
+fn skipped_letter_unused() {
+    let words = ["ab", "ba", "aba", "bab"]
+        .iter()
+        .map(|s| str_to_word(s))
+        .collect::<Vec<_>>();
+    for (word, sym) in variants_on(&words, 3) {
+        assert!(!word.contains(&sym));
+    }
+}
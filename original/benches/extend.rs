@@ -0,0 +1,59 @@
+//
+// Benchmarks for the union-find search: extending every word found so
+// far by one letter.
+//
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+
+use monoid_gen::{extend, ExtendOptions, RepStrategy, Sym, Union};
+
+use std::thread;
+
+const GENERATORS: Sym = 3;
+
+// A search over 3 generators that has got as far as words of the given
+// length.
+fn searched_to(max_length: usize) -> Union {
+    let mut u = Union::new(RepStrategy::Shortest);
+    for i in 0..GENERATORS {
+        u.key_for(&[i]);
+    }
+    while u.iterations() < max_length {
+        extend(&mut u, GENERATORS, &[], &ExtendOptions::default());
+    }
+    u
+}
+
+// One more iteration of the search, on one thread and then on as many
+// as there are cores. Throughput is in words extended.
+fn bench_extend(c: &mut Criterion) {
+    let n_threads = thread::available_parallelism().map_or(1, |n| n.get());
+    let mut group = c.benchmark_group("extend");
+    group.sample_size(10);
+    for len in [12, 14] {
+        let u = searched_to(len);
+        let prev = searched_to(len - 1);
+        group.throughput(Throughput::Elements(
+            (u.words().len() - prev.words().len()) as u64,
+        ));
+        for (label, threads) in [("serial", 1), ("parallel", n_threads)] {
+            group.bench_with_input(BenchmarkId::new(label, len), &u, |b, u| {
+                b.iter_batched(
+                    || u.clone(),
+                    |mut u| {
+                        let options = ExtendOptions {
+                            n_threads: threads,
+                            ..ExtendOptions::default()
+                        };
+                        extend(&mut u, GENERATORS, &[], &options)
+                    },
+                    BatchSize::LargeInput,
+                )
+            });
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_extend);
+criterion_main!(benches);
@@ -0,0 +1,719 @@
+//
+// monoid-gen: Brute-force search for the elements of a small
+// idempotent free monoid, by joining words into equivalence classes
+// with their square roots using union-find. The search itself lives
+// here, so it can be benchmarked; the binary is the command line on
+// top.
+//
+
+use clap::ValueEnum;
+use idem_monoid::Alphabet;
+
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+////////////////////////////////////////////////////////////////////////
+// Helpers
+//
+
+pub type Sym = u8;
+pub type Word = Vec<Sym>;
+pub type WordRef<'a> = &'a [Sym];
+
+/// Write a word with the letters 'a' to 'z'.
+pub fn syms_to_str(v: WordRef) -> String {
+    Alphabet::lowercase().encode(v)
+}
+
+/// Convert a string to a word, returning the position and character
+/// of the first character that isn't a lower-case letter.
+pub fn str_to_syms(s: &str) -> Result<Word, (usize, char)> {
+    Alphabet::lowercase()
+        .decode(s)
+        .map_err(|e| (e.position, e.letter))
+}
+
+////////////////////////////////////////////////////////////////////////
+// Union find
+//
+
+/// Index of a word in the union-find structure.
+pub type Key = u32;
+
+/// How to choose which word represents an equivalence class.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum RepStrategy {
+    /// The shortest word, breaking ties on the lexicographically least
+    Shortest,
+    /// The lexicographically least word, whatever its length
+    Lexicographic,
+    /// The word the search found first
+    FirstSeen,
+}
+
+/// Implement union-find ourselves, yet again. Uses union by size and
+/// path compression, keeping track of the representative word of each
+/// class separately, as chosen by the strategy.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Union {
+    // Map things to keys.
+    rep_map: HashMap<Word, Key>,
+    // And back.
+    rev_map: Vec<Word>,
+    // Map keys to other keys.
+    ptrs: Vec<Key>,
+    // Number of elements in the class, for root keys.
+    sizes: Vec<Key>,
+    // Key of the representative word of the class, for root keys.
+    reps: Vec<Key>,
+    // How the representatives are chosen.
+    strategy: RepStrategy,
+    // Number of times the search has been extended.
+    iterations: usize,
+    // Number of words (in key order) that have already been extended
+    // by every letter. Not checkpointed, so a resumed search extends
+    // everything again once.
+    extended: usize,
+}
+
+impl Union {
+    /// An empty structure, choosing representatives with the given
+    /// strategy.
+    pub fn new(strategy: RepStrategy) -> Union {
+        // Initially, all pointers point to themselves.
+        Union {
+            rep_map: HashMap::new(),
+            rev_map: Vec::new(),
+            ptrs: Vec::new(),
+            sizes: Vec::new(),
+            reps: Vec::new(),
+            strategy,
+            iterations: 0,
+            extended: 0,
+        }
+    }
+
+    /// The key of a word, adding it in a class of its own if it's new.
+    pub fn key_for(&mut self, v: WordRef) -> Key {
+        self.key_for_owned(v.to_vec())
+    }
+
+    // Like 'key_for', but taking the word, to save copying it when
+    // it's already been seen.
+    fn key_for_owned(&mut self, v: Word) -> Key {
+        match self.rep_map.entry(v) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                let i = self.rev_map.len() as Key;
+                self.rev_map.push(entry.key().clone());
+                self.ptrs.push(i);
+                self.sizes.push(1);
+                self.reps.push(i);
+                entry.insert(i);
+                i
+            }
+        }
+    }
+
+    // Find the root of the key's class, without updating anything.
+    fn root_of(&self, mut key: Key) -> Key {
+        while self.ptrs[key as usize] != key {
+            key = self.ptrs[key as usize];
+        }
+        key
+    }
+
+    // Find the root of the key's class, and point everything on the
+    // way directly at it.
+    fn find(&mut self, mut key: Key) -> Key {
+        let root = self.root_of(key);
+        while self.ptrs[key as usize] != root {
+            let next = self.ptrs[key as usize];
+            self.ptrs[key as usize] = root;
+            key = next;
+        }
+        root
+    }
+
+    /// Join the classes of the two keys.
+    pub fn union(&mut self, idx1: Key, idx2: Key) {
+        let tgt1 = self.find(idx1);
+        let tgt2 = self.find(idx2);
+        if tgt1 == tgt2 {
+            return;
+        }
+
+        let rep = self.better_rep(self.reps[tgt1 as usize], self.reps[tgt2 as usize]);
+
+        // Hang the smaller class off the bigger one.
+        let (big, small) = if self.sizes[tgt1 as usize] < self.sizes[tgt2 as usize] {
+            (tgt2, tgt1)
+        } else {
+            (tgt1, tgt2)
+        };
+        self.ptrs[small as usize] = big;
+        self.sizes[big as usize] += self.sizes[small as usize];
+        self.reps[big as usize] = rep;
+    }
+
+    // Pick whichever of the two words the strategy prefers as a
+    // representative. Each strategy is a total order on words, so the
+    // representative doesn't depend on the order of unions.
+    fn better_rep(&self, key1: Key, key2: Key) -> Key {
+        let word1 = &self.rev_map[key1 as usize];
+        let word2 = &self.rev_map[key2 as usize];
+        let first = match self.strategy {
+            RepStrategy::Shortest => (word1.len(), word1) < (word2.len(), word2),
+            RepStrategy::Lexicographic => word1 < word2,
+            RepStrategy::FirstSeen => key1 < key2,
+        };
+        if first {
+            key1
+        } else {
+            key2
+        }
+    }
+
+    /// The number of times the search has been extended. As it starts
+    /// from the single letters, the longest words searched are one
+    /// letter longer than this.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// The words searched, indexed by key.
+    pub fn words(&self) -> &[Word] {
+        &self.rev_map
+    }
+
+    /// The key of a word, if it's been searched.
+    pub fn key_of(&self, v: WordRef) -> Option<Key> {
+        self.rep_map.get(v).copied()
+    }
+
+    /// Chase the pointers, to get the word representing the
+    /// equivalence class, as chosen by the strategy.
+    pub fn rep_of(&self, key: Key) -> Word {
+        let root = self.root_of(key);
+        self.rev_map[self.reps[root as usize] as usize].clone()
+    }
+
+    /// Count the equivalence classes, without building them.
+    pub fn num_classes(&self) -> usize {
+        (0..self.ptrs.len() as Key)
+            .filter(|key| self.ptrs[*key as usize] == *key)
+            .count()
+    }
+
+    /// The length of the representative of each equivalence class,
+    /// again without building the classes.
+    pub fn rep_lengths(&self) -> Vec<usize> {
+        (0..self.ptrs.len() as Key)
+            .filter(|key| self.ptrs[*key as usize] == *key)
+            .map(|key| self.rev_map[self.reps[key as usize] as usize].len())
+            .collect()
+    }
+
+    /// Get the equivalence classes, each paired with its
+    /// representative, as returned by 'rep_of'. Sorted by the classes'
+    /// members.
+    pub fn to_sets_with_reps(&self) -> Vec<(Word, Vec<Word>)> {
+        let mut mapping: HashMap<Key, Vec<Key>> = HashMap::new();
+        for idx in 0..self.ptrs.len() as Key {
+            mapping.entry(self.root_of(idx)).or_default().push(idx)
+        }
+
+        let convert = |set_num: &Key| self.rev_map[*set_num as usize].clone();
+
+        let mut sets = mapping
+            .iter()
+            .map(|(root, set_list)| {
+                let mut set = set_list.iter().map(convert).collect::<Vec<_>>();
+                set.sort();
+                (self.rep_of(*root), set)
+            })
+            .collect::<Vec<_>>();
+        sets.sort_by(|(_, a), (_, b)| a.cmp(b));
+        sets
+    }
+
+    /// Write the state out to a file, so that a search can be resumed
+    /// later. The format is a simple sequence of little-endian
+    /// integers: a header giving the number of generators, the
+    /// representative strategy and the extra relations the search was
+    /// run with, then the iteration count, the number of words, and
+    /// for each word its length, symbols, pointer, size and
+    /// representative key. The word to key map is rebuilt on load.
+    pub fn save(&self, path: &Path, generators: Sym, relations: &[Relation]) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+        out.write_all(CHECKPOINT_MAGIC)?;
+        out.write_all(&[generators, self.strategy as u8])?;
+        out.write_all(&(relations.len() as u32).to_le_bytes())?;
+        for (lhs, rhs) in relations.iter() {
+            for side in [lhs, rhs] {
+                out.write_all(&(side.len() as u32).to_le_bytes())?;
+                out.write_all(side)?;
+            }
+        }
+        out.write_all(&(self.iterations as u64).to_le_bytes())?;
+        out.write_all(&(self.rev_map.len() as u64).to_le_bytes())?;
+        for (idx, word) in self.rev_map.iter().enumerate() {
+            out.write_all(&(word.len() as u32).to_le_bytes())?;
+            out.write_all(word)?;
+            out.write_all(&self.ptrs[idx].to_le_bytes())?;
+            out.write_all(&self.sizes[idx].to_le_bytes())?;
+            out.write_all(&self.reps[idx].to_le_bytes())?;
+        }
+        out.flush()
+    }
+
+    /// Read back a state written by 'save', checking it was written
+    /// by a search with the same options, and that it's well-formed
+    /// enough not to panic later.
+    pub fn load(
+        path: &Path,
+        strategy: RepStrategy,
+        generators: Sym,
+        relations: &[Relation],
+    ) -> io::Result<Union> {
+        let mut input = BufReader::new(File::open(path)?);
+        let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let mut magic = [0; CHECKPOINT_MAGIC.len()];
+        input.read_exact(&mut magic)?;
+        if magic != *CHECKPOINT_MAGIC {
+            return Err(invalid(String::from("not a checkpoint file")));
+        }
+
+        fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+            let mut buf = [0; 8];
+            input.read_exact(&mut buf)?;
+            Ok(u64::from_le_bytes(buf))
+        }
+        fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+            let mut buf = [0; 4];
+            input.read_exact(&mut buf)?;
+            Ok(u32::from_le_bytes(buf))
+        }
+        fn read_word(input: &mut impl Read) -> io::Result<Word> {
+            let mut word = vec![0; read_u32(input)? as usize];
+            input.read_exact(&mut word)?;
+            Ok(word)
+        }
+
+        let mut header = [0; 2];
+        input.read_exact(&mut header)?;
+        let [saved_generators, saved_strategy] = header;
+        if saved_generators != generators {
+            return Err(invalid(format!(
+                "written with {} generators, not {}",
+                saved_generators, generators
+            )));
+        }
+        if saved_strategy != strategy as u8 {
+            return Err(invalid(String::from(
+                "written with a different representative strategy",
+            )));
+        }
+        let mut saved_relations = Vec::new();
+        for _ in 0..read_u32(&mut input)? {
+            saved_relations.push((read_word(&mut input)?, read_word(&mut input)?));
+        }
+        if saved_relations != relations {
+            return Err(invalid(String::from("written with different relations")));
+        }
+
+        let mut u = Union::new(strategy);
+        u.iterations = read_u64(&mut input)? as usize;
+        let len = read_u64(&mut input)? as usize;
+        if len > Key::MAX as usize {
+            return Err(invalid(format!("too many words ({})", len)));
+        }
+        for idx in 0..len {
+            let word = read_word(&mut input)?;
+            if word.iter().any(|sym| *sym >= generators) {
+                return Err(invalid(format!("word {} uses too many letters", idx)));
+            }
+            if u.rep_map.insert(word.clone(), idx as Key).is_some() {
+                return Err(invalid(format!("word {} is repeated", idx)));
+            }
+            u.rev_map.push(word);
+            u.ptrs.push(read_u32(&mut input)?);
+            u.sizes.push(read_u32(&mut input)?);
+            u.reps.push(read_u32(&mut input)?);
+        }
+
+        // Check the keys all point at other keys, and that following
+        // the pointers reaches a root. Union by size keeps the paths
+        // shorter than the number of bits in a key, so anything
+        // longer is a cycle.
+        for key in 0..len {
+            if u.ptrs[key] as usize >= len || u.reps[key] as usize >= len {
+                return Err(invalid(format!("key {} out of range", key)));
+            }
+        }
+        for key in 0..len as Key {
+            let mut root = key;
+            for _ in 0..=Key::BITS {
+                root = u.ptrs[root as usize];
+            }
+            if u.ptrs[root as usize] != root {
+                return Err(invalid(format!("key {} doesn't lead to a root", key)));
+            }
+        }
+        Ok(u)
+    }
+}
+
+const CHECKPOINT_MAGIC: &[u8; 8] = b"UNIONV2\n";
+
+////////////////////////////////////////////////////////////////////////
+// Progress reporting
+//
+
+// A single, updating line on stderr showing how far through a phase
+// we are, and roughly how long is left. Does nothing if disabled.
+struct Progress {
+    enabled: bool,
+    label: String,
+    total: usize,
+    start: Instant,
+    last_update: Instant,
+}
+
+impl Progress {
+    // Minimum time between redraws.
+    const INTERVAL: Duration = Duration::from_millis(200);
+
+    fn new(enabled: bool, label: String, total: usize) -> Progress {
+        let now = Instant::now();
+        Progress {
+            enabled,
+            label,
+            total,
+            start: now,
+            last_update: now,
+        }
+    }
+
+    fn update(&mut self, done: usize) {
+        if !self.enabled || self.last_update.elapsed() < Progress::INTERVAL {
+            return;
+        }
+        self.last_update = Instant::now();
+
+        let elapsed = self.start.elapsed().as_secs_f64();
+        let eta = if done == 0 {
+            0.0
+        } else {
+            elapsed * (self.total - done) as f64 / done as f64
+        };
+        eprint!(
+            "\r{}: {}/{} ({:.0}%, ETA {:.0}s)   ",
+            self.label,
+            done,
+            self.total,
+            100.0 * done as f64 / self.total.max(1) as f64,
+            eta
+        );
+    }
+
+    fn finish(&self) {
+        if self.enabled {
+            eprintln!(
+                "\r{}: {}/{} (done in {:.1}s)   ",
+                self.label,
+                self.total,
+                self.total,
+                self.start.elapsed().as_secs_f64()
+            );
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////
+// The search
+//
+
+/// Find all sub-squares, and call the function on each word with the
+/// square replaced by its root.
+pub fn for_each_square_root(word: WordRef, mut f: impl FnMut(Word)) {
+    for len in 2..=word.len() / 2 {
+        for idx in 0..=word.len() - 2 * len {
+            if word[idx..][..len] == word[idx + len..][..len] {
+                let mut reduced_word = word[..idx].to_vec();
+                reduced_word.extend(&word[idx + len..]);
+                f(reduced_word);
+            }
+        }
+    }
+}
+
+/// An extra relation, saying the two words are equal.
+pub type Relation = (Word, Word);
+
+/// Parse a relation written as two words either side of '='. Repeated
+/// letters are collapsed, as the search never generates words
+/// containing them.
+pub fn parse_relation(s: &str) -> Result<Relation, String> {
+    let (lhs, rhs) = s
+        .split_once('=')
+        .ok_or_else(|| String::from("expected two words either side of '='"))?;
+    let parse = |side: &str| {
+        if side.is_empty() {
+            return Err(String::from("the empty word can't be used in a relation"));
+        }
+        let mut word = str_to_syms(side).map_err(|(_, c)| format!("invalid character '{}'", c))?;
+        word.dedup();
+        Ok(word)
+    };
+    Ok((parse(lhs)?, parse(rhs)?))
+}
+
+/// Rewrite each place either side of a relation appears in the word
+/// with the other side, and call the function on each rewritten word.
+/// Repeated letters are then collapsed, as the search never generates
+/// words containing them.
+///
+/// Rewrites that replace a side with a longer one are left out, as
+/// they'd add words longer than the search has reached. `extend`
+/// makes them once the search is long enough.
+pub fn for_each_rewrite(word: WordRef, relations: &[Relation], mut f: impl FnMut(Word)) {
+    for (from, to) in rewrite_rules(relations) {
+        if to.len() <= from.len() {
+            for_each_replacement(word, from, to, &mut f);
+        }
+    }
+}
+
+// Each side of each relation, paired with the side it's rewritten to.
+fn rewrite_rules(relations: &[Relation]) -> impl Iterator<Item = (&Word, &Word)> {
+    relations
+        .iter()
+        .flat_map(|(lhs, rhs)| [(lhs, rhs), (rhs, lhs)])
+}
+
+// Replace each place 'from' appears in the word with 'to', and call
+// the function on each result, with repeated letters collapsed.
+fn for_each_replacement(word: WordRef, from: WordRef, to: WordRef, f: &mut impl FnMut(Word)) {
+    for idx in 0..(word.len() + 1).saturating_sub(from.len()) {
+        if word[idx..].starts_with(from) {
+            let mut rewritten = word[..idx].to_vec();
+            rewritten.extend(to);
+            rewritten.extend(&word[idx + from.len()..]);
+            rewritten.dedup();
+            f(rewritten);
+        }
+    }
+}
+
+// Make the rewrites 'for_each_rewrite' leaves out, that lengthen a
+// word, for the words they take to the length just reached. Shorter
+// words were done on earlier iterations, and longer ones wait for
+// later ones.
+fn add_lengthening_rewrites(u: &mut Union, relations: &[Relation]) {
+    let max_len = u.iterations + 1;
+    for (from, to) in rewrite_rules(relations) {
+        if to.len() <= from.len() || max_len + from.len() < to.len() {
+            continue;
+        }
+        let len = max_len + from.len() - to.len();
+        for key in 0..u.rev_map.len() {
+            if u.rev_map[key].len() == len {
+                let word = u.rev_map[key].clone();
+                for_each_replacement(&word, from, to, &mut |rewritten| {
+                    let k2 = u.key_for_owned(rewritten);
+                    u.union(key as Key, k2);
+                });
+            }
+        }
+    }
+}
+
+/// Add the word, joining it to its square roots and its rewrites by
+/// the extra relations.
+pub fn register(u: &mut Union, word: WordRef, relations: &[Relation]) {
+    let k = u.key_for(word);
+    // Find all sub-squares, and union with square roots.
+    for_each_square_root(word, |reduced_word| {
+        let k2 = u.key_for(&reduced_word);
+        u.union(k, k2);
+    });
+    // And the same for the extra relations.
+    for_each_rewrite(word, relations, |rewritten| {
+        let k2 = u.key_for(&rewritten);
+        u.union(k, k2);
+    });
+}
+
+// Generate the one-letter extensions of the given words, along with
+// their square roots and rewrites by the extra relations.
+fn extensions(words: &[Word], n_syms: Sym, relations: &[Relation]) -> Vec<(Word, Vec<Word>)> {
+    let mut res = Vec::new();
+    for elt in words.iter() {
+        let last = *elt.last().unwrap();
+        for sym in 0..n_syms {
+            if last != sym {
+                let mut new = elt.to_vec();
+                new.push(sym);
+                let mut roots = Vec::new();
+                for_each_square_root(&new, |root| roots.push(root));
+                for_each_rewrite(&new, relations, |rewritten| roots.push(rewritten));
+                res.push((new, roots));
+            }
+        }
+    }
+    res
+}
+
+// Add a word generated by 'extensions' to the union-find structure,
+// joined to its roots and rewrites.
+fn add_extension(u: &mut Union, word: Word, roots: impl Iterator<Item = Word>) {
+    let k = u.key_for_owned(word);
+    for reduced_word in roots {
+        let k2 = u.key_for_owned(reduced_word);
+        u.union(k, k2);
+    }
+}
+
+// Number of words to extend in parallel before adding the results to
+// the union-find structure, to bound memory use.
+const BATCH_SIZE: usize = 1 << 16;
+
+/// How `extend` goes about extending the words, as opposed to what it
+/// searches.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExtendOptions {
+    /// Show progress on stderr.
+    pub show_progress: bool,
+    /// Report how long finding squares and updating the union-find
+    /// structure took on stderr.
+    pub time: bool,
+    /// Extend every word found, not just those added since the last
+    /// iteration.
+    pub from_scratch: bool,
+    /// Number of threads to find squares with.
+    pub n_threads: usize,
+}
+
+impl Default for ExtendOptions {
+    fn default() -> ExtendOptions {
+        ExtendOptions {
+            show_progress: false,
+            time: false,
+            from_scratch: false,
+            n_threads: 1,
+        }
+    }
+}
+
+/// Extend all the words found so far by one letter. With more than one
+/// thread, the new words are built in batches, with their square roots
+/// found across the threads, while updating the union-find structure
+/// is done serially, in the same order as a single-threaded run, so
+/// the results don't depend on the number of threads.
+///
+/// The batched path isn't the default, as it hasn't been shown to pay
+/// off. Measured with `count-classes --max-length 20` on 3 generators,
+/// on a single core, the serial path takes 16-19s, of which finding
+/// squares is 4-5s, and the batched path with 4 threads 32-36s. The
+/// union-find updates take 9-11s serially and can't be split across
+/// threads, so even with many cores the batched path could save at
+/// most the 4-5s of finding squares. Run `cargo bench --bench extend`
+/// on a multi-core machine before making it the default.
+///
+/// If timing, the time spent finding squares and updating the
+/// union-find structure is reported separately, for whichever path is
+/// taken.
+pub fn extend(u: &mut Union, n_syms: Sym, relations: &[Relation], options: &ExtendOptions) {
+    let ExtendOptions {
+        show_progress,
+        time,
+        from_scratch,
+        n_threads,
+    } = *options;
+    u.iterations += 1;
+    // Extending a word always registers the same new words, so only
+    // the words added since the last iteration need extending, unless
+    // we're asked to do it all again to check.
+    let first = if from_scratch { 0 } else { u.extended };
+    let len = u.rev_map.len();
+    u.extended = len;
+    let mut progress = Progress::new(
+        show_progress,
+        format!("Extend {}", u.iterations),
+        len - first,
+    );
+
+    let mut square_time = Duration::ZERO;
+    let mut union_time = Duration::ZERO;
+    if n_threads == 1 {
+        // Don't bother buffering up the work, just reuse the space
+        // for each word's roots.
+        let mut roots = Vec::new();
+        for idx in first..len {
+            if (idx - first) % 1024 == 0 {
+                progress.update(idx - first);
+            }
+            let elt = u.rev_map[idx].clone();
+            let last = *elt.last().unwrap();
+            for sym in 0..n_syms {
+                if last != sym {
+                    let start = Instant::now();
+                    let mut new = elt.to_vec();
+                    new.push(sym);
+                    roots.clear();
+                    for_each_square_root(&new, |root| roots.push(root));
+                    for_each_rewrite(&new, relations, |rewritten| roots.push(rewritten));
+                    square_time += start.elapsed();
+
+                    let start = Instant::now();
+                    add_extension(u, new, roots.drain(..));
+                    union_time += start.elapsed();
+                }
+            }
+        }
+    } else {
+        for batch_start in (first..len).step_by(BATCH_SIZE) {
+            let start = Instant::now();
+            let batch_end = len.min(batch_start + BATCH_SIZE);
+            let batch = &u.rev_map[batch_start..batch_end];
+            let chunk_size = batch.len().div_ceil(n_threads);
+            let candidates = thread::scope(|s| {
+                batch
+                    .chunks(chunk_size)
+                    .map(|chunk| s.spawn(move || extensions(chunk, n_syms, relations)))
+                    .collect::<Vec<_>>()
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .collect::<Vec<_>>()
+            });
+            square_time += start.elapsed();
+
+            let start = Instant::now();
+            for (word, roots) in candidates.into_iter().flatten() {
+                add_extension(u, word, roots.into_iter());
+            }
+            union_time += start.elapsed();
+            progress.update(batch_end - first);
+        }
+    }
+    let start = Instant::now();
+    add_lengthening_rewrites(u, relations);
+    union_time += start.elapsed();
+    progress.finish();
+
+    if time {
+        eprintln!(
+            "Iteration {}: squares {:.3}s, union {:.3}s",
+            u.iterations,
+            square_time.as_secs_f64(),
+            union_time.as_secs_f64()
+        );
+    }
+}
@@ -1,317 +1,658 @@
-use std::collections::HashMap;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use idem_monoid::{product, ConcatMode, DEFAULT_EMPTY_SYMBOL};
+use monoid_gen::{
+    extend, for_each_square_root, parse_relation, register, str_to_syms, syms_to_str,
+    ExtendOptions, Key, Relation, RepStrategy, Sym, Union, Word,
+};
+
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::iter;
+use std::path::PathBuf;
+use std::process;
+use std::time::Instant;
 
 ////////////////////////////////////////////////////////////////////////
-// Helpers
+// Main code.
 //
 
-type Sym = u8;
-type Word = Vec<Sym>;
-type WordRef<'a> = &'a [Sym];
-
-fn sym_to_c(i: Sym) -> char {
-    char::from_digit(i as u32 + 10, 36).unwrap()
+// Print each class, optionally with its representative.
+fn pretty_print_sets(sets: &[(Word, Vec<Word>)], show_rep: bool) {
+    for (rep, set) in sets.iter() {
+        let words = set.iter().map(|sym| syms_to_str(sym)).collect::<Vec<_>>();
+        if show_rep {
+            println!("{}: {}", syms_to_str(rep), words.join(", "));
+        } else {
+            println!("{}", words.join(", "));
+        }
+    }
 }
 
-fn syms_to_str(v: WordRef) -> String {
-    v.iter().map(|c| sym_to_c(*c)).collect::<String>()
+// Get the equivalence classes, reporting how long it took if asked.
+fn timed_sets(u: &Union, time: bool) -> Vec<(Word, Vec<Word>)> {
+    let start = Instant::now();
+    let sets = u.to_sets_with_reps();
+    if time {
+        eprintln!(
+            "Iteration {}: to_sets {:.3}s",
+            u.iterations(),
+            start.elapsed().as_secs_f64()
+        );
+    }
+    sets
 }
 
-fn c_to_sym(c: char) -> Sym {
-    c.to_digit(36).unwrap() as Sym - 10
-}
+// Returns a cumulative histogram, where the nth element is the number
+// of equivalence classes containing where each class contains a word
+// smaller than or equal to n.
+fn cumulative_histogram(v: &[usize]) -> Vec<usize> {
+    // Collect counts of each min-size.
+    let len = v.iter().max().unwrap() + 1;
+    let mut counts = vec![0; len];
 
-fn str_to_syms(s: &str) -> Word {
-    s.chars().map(c_to_sym).collect::<Vec<_>>()
-}
+    // Hack: We don't include the empty string in our search, because
+    // concatenating empty strings onto everything, squaring empty
+    // string etc. is a waste of time. Instead, we'll hack it into the
+    // count here!
+    counts[0] = 1;
 
-////////////////////////////////////////////////////////////////////////
-// Union find
-//
+    for elt in v.iter() {
+        counts[*elt] += 1;
+    }
+
+    // Convert to cumulative.
+    let mut c = 0;
+    for count in counts.iter_mut() {
+        c += *count;
+        *count = c;
+    }
 
-type Key = u32;
-
-// Implement union-find ourselves, yet again.
-#[derive(Debug, Clone, Eq, PartialEq)]
-struct Union {
-    // Map things to keys.
-    rep_map: HashMap<Word, Key>,
-    // And back.
-    rev_map: Vec<Word>,
-    // Map keys to other keys.
-    ptrs: Vec<Key>,
+    counts
 }
 
-impl Union {
-    fn new() -> Union {
-        // Initially, all pointers point to themselves.
-        Union {
-            rep_map: HashMap::new(),
-            rev_map: Vec::new(),
-            ptrs: Vec::new(),
+// Extend the search by one more letter, checkpointing if requested.
+fn step(u: &mut Union, options: &Options) {
+    extend(
+        u,
+        options.generators,
+        &options.relation,
+        &ExtendOptions {
+            show_progress: options.progress,
+            time: options.time,
+            from_scratch: options.from_scratch,
+            n_threads: usize::from(options.threads),
+        },
+    );
+    if let Some(path) = &options.checkpoint {
+        let start = Instant::now();
+        let saved = u.save(path, options.generators, &options.relation);
+        if options.time {
+            eprintln!(
+                "Iteration {}: checkpoint {:.3}s",
+                u.iterations(),
+                start.elapsed().as_secs_f64()
+            );
+        }
+        if let Err(e) = saved {
+            eprintln!("Couldn't write checkpoint {}: {}", path.display(), e);
+            process::exit(1);
         }
     }
+}
 
-    fn key_for(&mut self, v: WordRef) -> Key {
-        *self.rep_map.entry(v.to_vec()).or_insert_with(|| {
-            let i = self.rev_map.len() as Key;
-            self.rev_map.push(v.to_vec());
-            self.ptrs.push(i);
-            i
-        })
+// Extend the search to words of up to the given length.
+fn search(u: &mut Union, options: &Options, max_length: usize) {
+    while u.iterations() < max_length {
+        step(u, options);
     }
+}
 
-    fn union(&mut self, mut idx1: Key, mut idx2: Key) {
-        // Not efficient, just get it done.
+// Get the equivalence classes, filtering out those that don't contain
+// a short word.
+fn short_sets(u: &Union, options: &Options, max_rep_len: usize) -> Vec<(Word, Vec<Word>)> {
+    timed_sets(u, options.time)
+        .into_iter()
+        .filter(|(_, set)| set.iter().any(|word| word.len() <= max_rep_len))
+        .collect::<Vec<_>>()
+}
 
-        // Dereference idx1's chain.
-        let mut tgt1 = idx1;
-        while self.ptrs[tgt1 as usize] != tgt1 {
-            assert!(self.ptrs[tgt1 as usize] < tgt1);
-            tgt1 = self.ptrs[tgt1 as usize];
-        }
-        // Dereference idx2's chain.
-        let mut tgt2 = idx2;
-        while self.ptrs[tgt2 as usize] != tgt2 {
-            assert!(self.ptrs[tgt2 as usize] < tgt2);
-            tgt2 = self.ptrs[tgt2 as usize];
-        }
-        // Use shortest word as target.
-        let tgt = if self.rev_map[tgt1 as usize].len() < self.rev_map[tgt2 as usize].len() {
-            tgt1
-        } else {
-            tgt2
-        };
-
-        // Repoint idx1's chain to target.
-        while self.ptrs[idx1 as usize] != idx1 {
-            let tmp = self.ptrs[idx1 as usize];
-            self.ptrs[idx1 as usize] = tgt;
-            idx1 = tmp;
-        }
-        self.ptrs[idx1 as usize] = tgt;
-        // Repoint idx2's chain to target.
-        while self.ptrs[idx2 as usize] != idx2 {
-            let tmp = self.ptrs[idx2 as usize];
-            self.ptrs[idx2 as usize] = tgt;
-            idx2 = tmp;
+// Generate cumulative histograms of the number of equivalence classes
+// as the search length increases.
+fn histogram(u: &mut Union, options: &Options, max_length: usize, format: HistogramFormat) {
+    // The cumulative histograms count the empty word, even though it
+    // isn't searched, so say so in the machine-readable formats.
+    match format {
+        HistogramFormat::Text => (),
+        HistogramFormat::Csv => {
+            println!("# Cumulative counts include the empty word, which isn't searched");
+            println!("length,classes,rep_length,cumulative_count");
         }
-        self.ptrs[idx2 as usize] = tgt;
+        HistogramFormat::Json => print!("["),
     }
 
-    // Chase the pointers, to get the word representing the
-    // equivalence class. As we always unify on the shortest word,
-    // this should be the shortest word in the equivalence class.
-    fn rep_of(&self, mut key: Key) -> Word {
-        while self.ptrs[key as usize] != key {
-            key = self.ptrs[key as usize];
+    // On my M1 Macbook: 23 takes 10 minutes, 22 takes 140s.
+    let first = u.iterations();
+    while u.iterations() < max_length {
+        step(u, options);
+        let i = u.iterations();
+        // We only need the length of each class's representative, which
+        // the union-find tracks, so avoid building the classes.
+        let start = Instant::now();
+        let num_classes = u.num_classes();
+        let min_elts = u.rep_lengths();
+        if options.time {
+            eprintln!(
+                "Iteration {}: count classes {:.3}s",
+                i,
+                start.elapsed().as_secs_f64()
+            );
+        }
+        let histogram = cumulative_histogram(&min_elts);
+        match format {
+            HistogramFormat::Text => {
+                println!("##### {} ({} entries, {:?})", i, num_classes, &histogram)
+            }
+            HistogramFormat::Csv => {
+                for (rep_len, count) in histogram.iter().enumerate() {
+                    println!("{},{},{},{}", i, num_classes, rep_len, count);
+                }
+            }
+            HistogramFormat::Json => {
+                let sep = if i == first + 1 { "" } else { "," };
+                let counts = histogram
+                    .iter()
+                    .map(|count| count.to_string())
+                    .collect::<Vec<_>>();
+                println!(
+                    "{}{{\"length\":{},\"classes\":{},\"cumulative\":[{}],\"includes_empty_word\":true}}",
+                    sep,
+                    i,
+                    num_classes,
+                    counts.join(",")
+                );
+            }
         }
-        self.rev_map[key as usize].clone()
     }
 
-    fn to_sets(&self) -> Vec<Vec<Word>> {
-        let mut mapping: HashMap<Key, Vec<Key>> = HashMap::new();
-        for (idx, tgt) in self.ptrs.iter().enumerate() {
-            mapping
-                .entry(*tgt)
-                .or_insert_with(Vec::new)
-                .push(idx as Key)
-        }
+    if format == HistogramFormat::Json {
+        println!("]");
+    }
+}
 
-        let convert = |set_num: &Key| self.rev_map[*set_num as usize].clone();
+// Print the number of equivalence classes found, not counting the
+// empty word, without building the classes.
+fn count_classes(u: &mut Union, options: &Options, max_length: usize) {
+    search(u, options, max_length);
+    println!("{}", u.num_classes());
+}
 
-        let mut sets = mapping
-            .values()
-            .map(|set_list| set_list.iter().map(convert).collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-        for set in sets.iter_mut() {
-            set.sort();
+// Print a Graphviz DOT graph of the words searched, with an edge from
+// each word to each of its square roots, as unioned by 'register'.
+// Nodes are coloured by equivalence class.
+fn graph_classes(u: &mut Union, options: &Options, max_length: usize) {
+    search(u, options, max_length);
+
+    // Number the classes in order of first appearance, to pick the
+    // colours.
+    let mut class_nums: HashMap<Word, usize> = HashMap::new();
+    println!("digraph classes {{");
+    println!("    node [style=filled, colorscheme=set312];");
+    for (key, word) in u.words().iter().enumerate() {
+        let rep = u.rep_of(key as Key);
+        let next = class_nums.len();
+        let class_num = *class_nums.entry(rep.clone()).or_insert(next);
+        println!(
+            "    \"{}\" [fillcolor={}, tooltip=\"{}\"];",
+            syms_to_str(word),
+            class_num % 12 + 1,
+            syms_to_str(&rep)
+        );
+    }
+    for word in u.words().iter() {
+        // Different squares can give the same root, but we only need
+        // one edge.
+        let mut roots = Vec::new();
+        for_each_square_root(word, |root| roots.push(root));
+        roots.sort();
+        roots.dedup();
+        for root in roots.iter() {
+            println!(
+                "    \"{}\" -> \"{}\";",
+                syms_to_str(word),
+                syms_to_str(root)
+            );
         }
-        sets.sort();
-        sets
     }
+    println!("}}");
 }
 
-////////////////////////////////////////////////////////////////////////
-// Main code.
-//
+// Print the shortest representatives of each element.
+fn generate(u: &mut Union, options: &Options, search_args: &SearchArgs) {
+    // Do the minimal work to find the 160 elements.
+    search(u, options, search_args.max_length);
 
-fn pretty_print_sets(sets: &[Vec<Word>]) {
-    for set in sets.iter() {
-        let words = set.iter().map(|sym| syms_to_str(sym)).collect::<Vec<_>>();
-        println!("{}", words.join(", "));
+    // Print each class's representative, which is the shortest word
+    // in it unless another --rep-strategy was asked for.
+    for (rep, _) in short_sets(u, options, search_args.max_rep_len).iter() {
+        println!("{}", syms_to_str(rep));
     }
 }
 
-const NUM_SYMS: Sym = 3;
-
-fn register(u: &mut Union, word: WordRef) {
-    let k = u.key_for(word);
-    // Find all sub-squares, and union with square roots.
-    for len in 2..=word.len() / 2 {
-        for idx in 0..=word.len() - 2 * len {
-            if word[idx..][..len] == word[idx + len..][..len] {
-                let mut reduced_word = word[..idx].to_vec();
-                reduced_word.extend(&word[idx + len..]);
-                let k2 = u.key_for(&reduced_word);
-                u.union(k, k2);
+// Let's generate the full "multiplication table":
+fn table(u: &mut Union, options: &Options, search_args: &SearchArgs, table_args: &TableArgs) {
+    // Do the minimal work to find the 160 elements.
+    search(u, options, search_args.max_length);
+
+    let sets = short_sets(u, options, search_args.max_rep_len);
+
+    // Use each class's representative as the element.
+    let reps = sets.into_iter().map(|(rep, _)| rep).collect::<Vec<_>>();
+
+    // The search doesn't include the empty word, so add it back in to
+    // get the full table.
+    let elements = iter::once(Vec::new()).chain(reps).collect::<Vec<_>>();
+
+    // Concatenate two words, avoiding repeated letters, as the search
+    // never generates them.
+    let concat = |i: &Word, j: &Word| -> Word { product(i, j, ConcatMode::DropBoundary) };
+
+    // Look up the representative of each product once, up front, so
+    // the table is built from lookups alone. Every product should
+    // already have been searched, or the search wasn't long enough to
+    // close the elements under multiplication.
+    let mut products: HashMap<Word, Word> = HashMap::new();
+    for i in elements.iter().filter(|i| !i.is_empty()) {
+        for j in elements.iter().filter(|j| !j.is_empty()) {
+            let ij = concat(i, j);
+            if products.contains_key(&ij) {
+                continue;
             }
+            let Some(ij_key) = u.key_of(&ij) else {
+                eprintln!(
+                    "The search isn't closed at length {}: {} * {} = {} wasn't searched. \
+                     Try a larger --max-length",
+                    u.iterations(),
+                    syms_to_str(i),
+                    syms_to_str(j),
+                    syms_to_str(&ij)
+                );
+                process::exit(1);
+            };
+            products.insert(ij, u.rep_of(ij_key));
         }
     }
-}
 
-fn extend(u: &mut Union) {
-    let len = u.rev_map.len();
-
-    for idx in 0..len {
-        let elt = u.rev_map[idx].clone();
-        let last = *elt.last().unwrap();
-        for sym in 0..NUM_SYMS {
-            if last != sym {
-                let mut new = elt.to_vec();
-                new.push(sym);
-                register(u, &new);
+    let product = |i: &Word, j: &Word| -> Word {
+        if i.is_empty() {
+            return j.clone();
+        }
+        if j.is_empty() {
+            return i.clone();
+        }
+        products[&concat(i, j)].clone()
+    };
+
+    match table_args.format {
+        TableFormat::Text => {
+            let reps = &elements[1..];
+            for i in reps.iter() {
+                for j in reps.iter() {
+                    let ij = product(i, j);
+                    println!(
+                        "{} * {} = {}",
+                        &syms_to_str(i),
+                        &syms_to_str(j),
+                        &syms_to_str(&ij)
+                    );
+                }
+            }
+        }
+        TableFormat::Csv => {
+            let to_str = |word: &Word| {
+                if word.is_empty() {
+                    table_args.empty_symbol.clone()
+                } else {
+                    syms_to_str(word)
+                }
+            };
+
+            let header = elements.iter().map(to_str).collect::<Vec<_>>();
+            println!(",{}", header.join(","));
+            for i in elements.iter() {
+                let row = elements
+                    .iter()
+                    .map(|j| to_str(&product(i, j)))
+                    .collect::<Vec<_>>();
+                println!("{},{}", to_str(i), row.join(","));
             }
         }
     }
 }
 
-// Returns a cumulative histogram, where the nth element is the number
-// of equivalence classes containing where each class contains a word
-// smaller than or equal to n.
-fn cumulative_histogram(v: &[usize]) -> Vec<usize> {
-    // Collect counts of each min-size.
-    let len = v.iter().max().unwrap() + 1;
-    let mut counts = vec![0; len];
+// Print all the equivalence classes that contain a short word.
+fn classes(u: &mut Union, options: &Options, search_args: &SearchArgs, class_args: &ClassArgs) {
+    search(u, options, search_args.max_length);
+    let mut sets = short_sets(u, options, search_args.max_rep_len);
+
+    match class_args.sort_by {
+        // Biggest classes first.
+        Some(SortBy::Size) => sets.sort_by_key(|(_, set)| Reverse(set.len())),
+        Some(SortBy::ReprLength) => sets.sort_by_key(|(rep, _)| rep.len()),
+        Some(SortBy::Repr) => sets.sort_by_key(|(rep, _)| (rep.len(), rep.clone())),
+        None => (),
+    }
 
-    // Hack: We don't include the empty string in our search, because
-    // concatenating empty strings onto everything, squaring empty
-    // string etc. is a waste of time. Instead, we'll hack it into the
-    // count here!
-    counts[0] = 1;
+    pretty_print_sets(&sets, class_args.show_rep);
+}
 
-    for elt in v.iter() {
-        counts[*elt] += 1;
+// Find the shortest word equivalent to the given word, as far as the
+// search can tell.
+fn reduce(u: &mut Union, options: &Options, search_args: &SearchArgs, word: &str) {
+    // Check the word before doing the slow bit.
+    let word = str_to_syms(word).unwrap_or_else(|(idx, c)| {
+        eprintln!(
+            "Invalid word '{}': character {} is '{}', expected 'a' to 'z'",
+            word,
+            idx + 1,
+            c
+        );
+        process::exit(1);
+    });
+
+    search(u, options, search_args.max_length);
+
+    // The search only joins a word to its direct square roots, and
+    // never contains repeated letters, so collapse those and register
+    // everything the word reduces to by removing squares. That way it
+    // is joined to whichever class the search put the shorter words in.
+    let mut word = word;
+    word.dedup();
+    let mut pending = vec![word.clone()];
+    let mut seen = HashSet::new();
+    while let Some(next) = pending.pop() {
+        if seen.insert(next.clone()) {
+            register(u, &next, &options.relation);
+            for_each_square_root(&next, |root| pending.push(root));
+        }
     }
+    let key = u.key_for(&word);
+    println!("{}", syms_to_str(&u.rep_of(key)));
+}
 
-    // Convert to cumulative.
-    let mut c = 0;
-    for count in counts.iter_mut() {
-        c += *count;
-        *count = c;
+// Check the representative the search finds for each short word
+// against the normal form from idem_monoid's reduction, which works
+// completely differently.
+fn cross_check(u: &mut Union, options: &Options, search_args: &SearchArgs, check_length: usize) {
+    // The normal form is the shortest word in the free idempotent
+    // monoid, so any other representatives, or a quotient, would
+    // disagree with it without anything being wrong.
+    if options.rep_strategy != RepStrategy::Shortest {
+        eprintln!("cross-check compares against normal forms, so needs --rep-strategy shortest");
+        process::exit(1);
+    }
+    if !options.relation.is_empty() {
+        eprintln!(
+            "cross-check compares against the free idempotent monoid, so can't use --relation"
+        );
+        process::exit(1);
     }
 
-    counts
-}
+    search(u, options, search_args.max_length);
 
-fn main() {
-    let mut u = Union::new();
+    let mut checked = 0;
+    let mut failures = 0;
+    for key in 0..u.words().len() as Key {
+        let word = &u.words()[key as usize];
+        if word.len() > check_length {
+            continue;
+        }
+        checked += 1;
+        let searched = u.rep_of(key);
+        let reduced = idem_monoid::reduce(word).end().clone();
+        if searched != reduced {
+            println!(
+                "{}: search gives {}, reduction gives {}",
+                syms_to_str(word),
+                syms_to_str(&searched),
+                syms_to_str(&reduced)
+            );
+            failures += 1;
+        }
+    }
 
-    for i in 0..NUM_SYMS {
-        u.key_for(&[i]);
+    println!("Checked {} words, {} disagreements", checked, failures);
+    if failures > 0 {
+        process::exit(1);
     }
+}
 
-    const GENERATE_HISTOGRAMS: bool = false;
-    const GENERATE_ELEMENTS: bool = false;
-
-    if GENERATE_HISTOGRAMS {
-        // Generate cumulative histograms of the number of equivalence
-        // classes as the search length increases.
-
-        // On my M1 Macbook: 23 takes 10 minutes, 22 takes 140s.
-        for i in 1..=22 {
-            extend(&mut u);
-            let sets = u.to_sets();
-            let min_elts = sets
-                .iter()
-                .map(|set| set.iter().map(|word| word.len()).min().unwrap())
-                .collect::<Vec<usize>>();
-            let histogram = cumulative_histogram(&min_elts);
-            println!("##### {} ({} entries, {:?})", i, sets.len(), &histogram);
-            // pretty_print_sets(&sets);
-        }
-    } else if GENERATE_ELEMENTS {
-        const MAX_LENGTH: usize = 20;
-        const MAX_REP_LEN: usize = 8;
+#[derive(Debug, Parser)]
+#[clap(name = "monoid-gen")]
+#[clap(about = "Brute-force search for the elements of a small idempotent free monoid", long_about = None)]
+struct Cli {
+    #[clap(flatten)]
+    options: Options,
 
-        // Do the minimal work to find the 160 elements.
-        for _ in 1..=MAX_LENGTH {
-            extend(&mut u);
-        }
+    #[clap(subcommand)]
+    command: Command,
+}
 
-        let sets = u.to_sets();
-
-        // Filter out equivalence classes that don't contain a short word.
-        let sets = sets
-            .iter()
-            .filter(|set| set.iter().map(|word| word.len()).min().unwrap() <= MAX_REP_LEN)
-            .collect::<Vec<_>>();
-
-        // For each equivalence class, find the shortest
-        // representations. Note we gather all equivalent shortest
-        // representations, just in case it turns out the shortest one
-        // isn't unique, which I think would be
-        // surpising/interesting... turns out it's unique.
-        fn reps(set: &[Word]) -> Vec<Word> {
-            let shortest = set.iter().map(|word| word.len()).min().unwrap();
-            set.iter()
-                .filter(|word| word.len() == shortest)
-                .map(|x| x.to_vec())
-                .collect::<Vec<_>>()
-        }
-        let shortests = sets.iter().map(|set| reps(set)).collect::<Vec<_>>();
-        for elt in shortests.iter() {
-            let stringified = elt.iter().map(|word| syms_to_str(word)).collect::<Vec<_>>();
+#[derive(Debug, Args)]
+struct Options {
+    /// Size of alphabet to search over, from 1 to 26
+    #[clap(long, value_parser = clap::value_parser!(Sym).range(1..=26), global = true, default_value_t = 3)]
+    generators: Sym,
+
+    /// File to save the search state to after each iteration
+    #[clap(long, value_parser, global = true)]
+    checkpoint: Option<PathBuf>,
+
+    /// File to resume the search from, as written by --checkpoint
+    #[clap(long, value_parser, global = true)]
+    resume: Option<PathBuf>,
+
+    /// Show progress of the search on stderr
+    #[clap(long, value_parser, global = true)]
+    progress: bool,
+
+    /// Show how long each phase of the search takes on stderr
+    #[clap(long, value_parser, global = true)]
+    time: bool,
+
+    /// Extend every word found on each iteration, not just the new
+    /// ones. Slower, but useful for checking
+    #[clap(long, value_parser, global = true)]
+    from_scratch: bool,
+
+    /// Number of threads to find squares with. More than one is only
+    /// worth trying on a machine with several cores
+    #[clap(long, value_parser = clap::value_parser!(u16).range(1..), global = true, default_value_t = 1)]
+    threads: u16,
+
+    /// An extra relation to search the quotient by, e.g. ab=ba. Can be
+    /// given more than once
+    #[clap(long, value_parser = parse_relation, global = true)]
+    relation: Vec<Relation>,
+
+    /// How to choose the word representing each class
+    #[clap(long, value_enum, global = true, default_value_t = RepStrategy::Shortest)]
+    rep_strategy: RepStrategy,
+}
 
-            println!("{}", stringified.join(", "));
-        }
-    } else {
-        // Let's generate the full "multiplication table":
-        const MAX_LENGTH: usize = 20;
-        const MAX_REP_LEN: usize = 8;
+#[derive(Debug, Args)]
+struct SearchArgs {
+    /// Length of the longest words to search
+    #[clap(long, value_parser, default_value_t = 20)]
+    max_length: usize,
 
-        // Do the minimal work to find the 160 elements.
-        for _ in 1..=MAX_LENGTH {
-            extend(&mut u);
-        }
+    /// Only keep equivalence classes with a word this short or shorter
+    #[clap(long, value_parser, default_value_t = 8)]
+    max_rep_len: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum HistogramFormat {
+    Text,
+    Csv,
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum TableFormat {
+    Text,
+    Csv,
+}
+
+#[derive(Debug, Args)]
+struct TableArgs {
+    /// Output format for the table
+    #[clap(long, value_enum, default_value_t = TableFormat::Text)]
+    format: TableFormat,
+
+    /// How to write the empty word in the table
+    #[clap(long, value_parser, default_value = DEFAULT_EMPTY_SYMBOL)]
+    empty_symbol: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum SortBy {
+    Size,
+    ReprLength,
+    Repr,
+}
+
+#[derive(Debug, Args)]
+struct ClassArgs {
+    /// Order to print the classes in
+    #[clap(long, value_enum)]
+    sort_by: Option<SortBy>,
 
-        let sets = u.to_sets();
+    /// Start each class with its representative
+    #[clap(long, value_parser)]
+    show_rep: bool,
+}
 
-        // Filter out equivalence classes that don't contain a short word.
-        let sets = sets
-            .iter()
-            .filter(|set| set.iter().map(|word| word.len()).min().unwrap() <= MAX_REP_LEN)
-            .collect::<Vec<_>>();
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print the number of equivalence classes found as the search
+    /// length increases
+    Histogram {
+        /// Length of the longest words to search
+        #[clap(long, value_parser, default_value_t = 22)]
+        max_length: usize,
+
+        /// Output format for the histogram
+        #[clap(long, value_enum, default_value_t = HistogramFormat::Text)]
+        format: HistogramFormat,
+    },
+    /// Print the number of equivalence classes found, not counting the
+    /// empty word
+    CountClasses {
+        /// Length of the longest words to search
+        #[clap(long, value_parser, default_value_t = 20)]
+        max_length: usize,
+    },
+    /// Print a DOT graph of the words searched, with edges from each
+    /// word to its square roots, coloured by equivalence class
+    GraphClasses {
+        /// Length of the longest words to search
+        #[clap(long, value_parser, default_value_t = 5)]
+        max_length: usize,
+    },
+    /// Print the shortest representatives of each element
+    Generate {
+        #[clap(flatten)]
+        search_args: SearchArgs,
+    },
+    /// Print the full multiplication table
+    Table {
+        #[clap(flatten)]
+        search_args: SearchArgs,
+
+        #[clap(flatten)]
+        table_args: TableArgs,
+    },
+    /// Print the equivalence classes found
+    Classes {
+        #[clap(flatten)]
+        search_args: SearchArgs,
+
+        #[clap(flatten)]
+        class_args: ClassArgs,
+    },
+    /// Check the search agrees with idem_monoid's reduction on short
+    /// words
+    CrossCheck {
+        #[clap(flatten)]
+        search_args: SearchArgs,
+
+        /// Length of the longest words to check
+        #[clap(long, value_parser, default_value_t = 8)]
+        check_length: usize,
+    },
+    /// Find the shortest word equivalent to the given word
+    Reduce {
+        #[clap(flatten)]
+        search_args: SearchArgs,
+
+        /// The word to reduce
+        #[clap(value_parser)]
+        word: String,
+    },
+}
 
-        // For each equivalence class, find the shortest representation.
-        fn rep(set: &[Word]) -> Word {
-            set.iter()
-                .min_by(|x, y| x.len().cmp(&y.len()))
-                .unwrap()
-                .to_vec()
+fn main() {
+    let args = Cli::parse();
+
+    let options = &args.options;
+    for (lhs, rhs) in options.relation.iter() {
+        if lhs.iter().chain(rhs).any(|sym| *sym >= options.generators) {
+            eprintln!(
+                "Relation {}={} uses letters beyond the {} generators",
+                syms_to_str(lhs),
+                syms_to_str(rhs),
+                options.generators
+            );
+            process::exit(1);
         }
-        let reps = sets.iter().map(|set| rep(set)).collect::<Vec<_>>();
-
-        let u_count = u.rev_map.len();
-        for i in reps.iter() {
-            for j in reps.iter() {
-                let mut ij = i.clone();
-                if i.last() == j.first() {
-                    // Avoid repeated letters.
-                    ij.extend(&j[1..]);
-                } else {
-                    ij.extend(j);
-                }
-                let ij_key = u.key_for(&ij);
-                let ij = u.rep_of(ij_key);
-                println!(
-                    "{} * {} = {}",
-                    &syms_to_str(i),
-                    &syms_to_str(j),
-                    &syms_to_str(&ij)
-                );
-            }
+    }
+
+    let mut u = if let Some(path) = &options.resume {
+        Union::load(
+            path,
+            options.rep_strategy,
+            options.generators,
+            &options.relation,
+        )
+        .unwrap_or_else(|e| {
+            eprintln!("Couldn't read checkpoint {}: {}", path.display(), e);
+            process::exit(1);
+        })
+    } else {
+        let mut u = Union::new(options.rep_strategy);
+        for i in 0..options.generators {
+            u.key_for(&[i]);
         }
-        // None of the words we've created should be new.
-        assert_eq!(u_count, u.rev_map.len());
+        u
+    };
+
+    match args.command {
+        Command::Histogram { max_length, format } => histogram(&mut u, options, max_length, format),
+        Command::CountClasses { max_length } => count_classes(&mut u, options, max_length),
+        Command::GraphClasses { max_length } => graph_classes(&mut u, options, max_length),
+        Command::Generate { search_args } => generate(&mut u, options, &search_args),
+        Command::Table {
+            search_args,
+            table_args,
+        } => table(&mut u, options, &search_args, &table_args),
+        Command::Classes {
+            search_args,
+            class_args,
+        } => classes(&mut u, options, &search_args, &class_args),
+        Command::CrossCheck {
+            search_args,
+            check_length,
+        } => cross_check(&mut u, options, &search_args, check_length),
+        Command::Reduce { search_args, word } => reduce(&mut u, options, &search_args, &word),
     }
 }
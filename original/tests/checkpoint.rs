@@ -0,0 +1,102 @@
+//
+// Check that a search resumed from a checkpoint finds the same classes
+// as one run straight through, and that checkpoints from a different
+// search are rejected.
+//
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+// Run monoid-gen with the given arguments.
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_monoid-gen"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+// A checkpoint file for the named test, out of the way of the others.
+fn checkpoint_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("monoid-gen-{}-{}.ckpt", name, std::process::id()))
+}
+
+#[test]
+fn resumed_matches_uninterrupted() {
+    let path = checkpoint_path("resume");
+    let path_str = path.to_str().unwrap();
+    let classes = ["classes", "--max-length", "10", "--max-rep-len", "11"];
+
+    let first = run(&[
+        "--checkpoint",
+        path_str,
+        "count-classes",
+        "--max-length",
+        "6",
+    ]);
+    assert!(first.status.success());
+    let resumed = run(&[&["--resume", path_str], &classes[..]].concat());
+    let uninterrupted = run(&classes);
+    fs::remove_file(&path).unwrap();
+
+    assert!(resumed.status.success());
+    assert!(!uninterrupted.stdout.is_empty());
+    assert_eq!(resumed.stdout, uninterrupted.stdout);
+}
+
+#[test]
+fn mismatched_options_are_rejected() {
+    let path = checkpoint_path("mismatch");
+    let path_str = path.to_str().unwrap();
+
+    let first = run(&[
+        "--checkpoint",
+        path_str,
+        "count-classes",
+        "--max-length",
+        "4",
+    ]);
+    assert!(first.status.success());
+    let others = [
+        run(&["--resume", path_str, "--generators", "2", "count-classes"]),
+        run(&[
+            "--resume",
+            path_str,
+            "--rep-strategy",
+            "first-seen",
+            "count-classes",
+        ]),
+        run(&["--resume", path_str, "--relation", "ab=ba", "count-classes"]),
+    ];
+    fs::remove_file(&path).unwrap();
+
+    for other in others.iter() {
+        assert!(!other.status.success());
+        let stderr = String::from_utf8_lossy(&other.stderr);
+        assert!(stderr.contains("Couldn't read checkpoint"));
+    }
+}
+
+#[test]
+fn truncated_checkpoint_is_an_error() {
+    let path = checkpoint_path("truncated");
+    let path_str = path.to_str().unwrap();
+
+    let first = run(&[
+        "--checkpoint",
+        path_str,
+        "count-classes",
+        "--max-length",
+        "4",
+    ]);
+    assert!(first.status.success());
+    let mut contents = fs::read(&path).unwrap();
+    contents.truncate(contents.len() - 3);
+    fs::write(&path, &contents).unwrap();
+    let resumed = run(&["--resume", path_str, "count-classes"]);
+    fs::remove_file(&path).unwrap();
+
+    assert!(!resumed.status.success());
+    assert!(!String::from_utf8_lossy(&resumed.stderr).contains("panicked"));
+}
@@ -0,0 +1,55 @@
+//
+// Check that cross-check only compares against normal forms when the
+// search's representatives should be normal forms.
+//
+
+use std::process::Command;
+
+// Run monoid-gen with the given arguments, returning whether it
+// succeeded and what it wrote to stderr.
+fn run(args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_monoid-gen"))
+        .args(args)
+        .output()
+        .unwrap();
+    (
+        output.status.success(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn agrees_with_reduction() {
+    let (ok, _) = run(&["cross-check", "--max-length", "10", "--check-length", "5"]);
+    assert!(ok);
+}
+
+#[test]
+fn other_representatives_are_an_error() {
+    let (ok, stderr) = run(&[
+        "--rep-strategy",
+        "lexicographic",
+        "cross-check",
+        "--max-length",
+        "10",
+        "--check-length",
+        "5",
+    ]);
+    assert!(!ok);
+    assert!(stderr.contains("--rep-strategy shortest"));
+}
+
+#[test]
+fn relations_are_an_error() {
+    let (ok, stderr) = run(&[
+        "--relation",
+        "ab=ba",
+        "cross-check",
+        "--max-length",
+        "10",
+        "--check-length",
+        "5",
+    ]);
+    assert!(!ok);
+    assert!(stderr.contains("can't use --relation"));
+}
@@ -0,0 +1,35 @@
+//
+// Check that extending only the new words each iteration finds the
+// same classes as extending everything.
+//
+
+use std::process::Command;
+
+// Run monoid-gen with the given arguments, returning its output.
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_monoid-gen"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn incremental_matches_from_scratch() {
+    for generators in ["2", "3"] {
+        let args = [
+            "--generators",
+            generators,
+            "classes",
+            "--max-length",
+            "12",
+            "--max-rep-len",
+            "13",
+        ];
+        let incremental = run(&args);
+        let from_scratch = run(&[&["--from-scratch"], &args[..]].concat());
+        assert!(!incremental.is_empty());
+        assert_eq!(incremental, from_scratch);
+    }
+}
@@ -0,0 +1,29 @@
+//
+// Check that the reduce subcommand finds the normal form of words the
+// search didn't reach directly.
+//
+
+use std::process::Command;
+
+// Run monoid-gen with the given arguments, returning its output.
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_monoid-gen"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn reduce_closes_squares() {
+    for (word, expected) in [
+        ("aab", "ab"),
+        ("aaa", "a"),
+        ("abcabcab", "abcab"),
+        ("cbabcabcb", "cbabcb"),
+    ] {
+        let output = run(&["reduce", "--max-length", "8", word]);
+        assert_eq!(output.trim(), expected);
+    }
+}
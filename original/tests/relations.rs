@@ -0,0 +1,76 @@
+//
+// Check that extra relations give the expected quotients.
+//
+
+use monoid_gen::{extend, parse_relation, str_to_syms, ExtendOptions, RepStrategy, Union};
+
+use std::process::Command;
+
+// Run monoid-gen with the given arguments, returning its output.
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_monoid-gen"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+// With every pair of generators commuting, the quotient is the free
+// semilattice, with an element per non-empty set of generators.
+#[test]
+fn commuting_generators_give_semilattice() {
+    let output = run(&[
+        "--relation",
+        "ab=ba",
+        "--relation",
+        "bc=cb",
+        "--relation",
+        "ac=ca",
+        "count-classes",
+        "--max-length",
+        "8",
+    ]);
+    assert_eq!(output.trim(), "7");
+}
+
+#[test]
+fn relations_apply_in_context() {
+    let output = run(&[
+        "--generators",
+        "2",
+        "--relation",
+        "ab=ba",
+        "reduce",
+        "babab",
+    ]);
+    assert_eq!(output.trim(), "ab");
+}
+
+// A relation with a longer side still only adds words as long as the
+// search has reached, whichever way round it's written, while joining
+// the words that need the longer side to show they're equal: with
+// a = bcb, ab = bcbb = bcb = a.
+#[test]
+fn longer_sides_stay_within_search() {
+    for relation in ["a=bcb", "bcb=a"] {
+        let relations = [parse_relation(relation).unwrap()];
+        let mut u = Union::new(RepStrategy::Shortest);
+        for i in 0..3 {
+            u.key_for(&[i]);
+        }
+        while u.iterations() < 6 {
+            extend(&mut u, 3, &relations, &ExtendOptions::default());
+            assert!(
+                u.words()
+                    .iter()
+                    .all(|word| word.len() <= u.iterations() + 1),
+                "{}",
+                relation
+            );
+        }
+        let a = u.key_for(&str_to_syms("a").unwrap());
+        let ab = u.key_for(&str_to_syms("ab").unwrap());
+        assert_eq!(u.rep_of(a), u.rep_of(ab), "{}", relation);
+    }
+}
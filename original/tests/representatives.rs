@@ -0,0 +1,48 @@
+//
+// Check that each class is represented by its shortest word, breaking
+// ties on the lexicographically least.
+//
+
+use std::process::Command;
+
+// Run monoid-gen with the given arguments, returning its output.
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_monoid-gen"))
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+// Check every class printed by 'classes --show-rep', returning the
+// representative of each.
+fn check_classes(options: &[&str], max_length: &str) -> Vec<String> {
+    let args = ["classes", "--show-rep", "--max-length", max_length];
+    let output = run(&[options, &args[..]].concat());
+    let mut reps = Vec::new();
+    for line in output.lines() {
+        let (rep, set) = line.split_once(": ").unwrap();
+        let least = set.split(", ").min_by_key(|word| (word.len(), *word));
+        assert_eq!(Some(rep), least, "class {}", line);
+        reps.push(rep.to_string());
+    }
+    reps
+}
+
+#[test]
+fn reps_are_least_shortest() {
+    let reps = check_classes(&[], "9");
+    for known in ["a", "ab", "aba", "abc", "abca", "bcab", "cbac"] {
+        assert!(reps.contains(&known.to_string()), "missing {}", known);
+    }
+}
+
+// With a relation, classes can contain more than one shortest word.
+#[test]
+fn ties_go_to_the_least() {
+    let reps = check_classes(&["--generators", "2", "--relation", "ab=ba"], "6");
+    assert_eq!(reps, vec!["a", "ab", "b"]);
+    let output = run(&["--generators", "2", "--relation", "ab=ba", "reduce", "bab"]);
+    assert_eq!(output.trim(), "ab");
+}
@@ -0,0 +1,17 @@
+//
+// Check that the table subcommand refuses to build a table from a
+// search too short to contain every product.
+//
+
+use std::process::Command;
+
+#[test]
+fn short_search_is_an_error() {
+    let output = Command::new(env!("CARGO_BIN_EXE_monoid-gen"))
+        .args(["table", "--max-length", "5"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("isn't closed at length 5"));
+}